@@ -0,0 +1,62 @@
+//! Compares the throughput of `fix_common_into`, which cleans most inputs
+//! in a single pass, with the full sequence of steps that `parse_common`
+//! runs on every input.
+//!
+//! Run with `cargo bench --bench fix_common`.
+//!
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use music_metadata_cleaner::{fix_common_into, parse_common};
+
+const ROWS: usize = 100_000;
+
+/// Builds the input row with the given index.
+type Row = fn(usize) -> String;
+
+/// Returns the rows cleaned per second by `clean`.
+fn throughput(rows: &[String], mut clean: impl FnMut(&str)) -> f64 {
+    let start = Instant::now();
+    for row in rows {
+        clean(black_box(row));
+    }
+    rows.len() as f64 / start.elapsed().max(Duration::from_nanos(1)).as_secs_f64()
+}
+
+fn main() {
+    let inputs: [(&str, Row); 3] = [
+        ("clean", |i| format!("Clean Title {}", i)),
+        ("annotated", |i| {
+            format!("Album {} (2019) [Mp3] (320 kbps)", i)
+        }),
+        ("mixed", |i| {
+            if i % 4 == 0 {
+                format!("Album {} (2019) [Mp3]", i)
+            } else {
+                format!("Clean Title {}", i)
+            }
+        }),
+    ];
+    println!(
+        "{:<10} {:>16} {:>16} {:>8}",
+        "input", "all steps/s", "single pass/s", "speedup"
+    );
+    for (name, row) in &inputs {
+        let rows: Vec<String> = (0..ROWS).map(row).collect();
+        let all_steps = throughput(&rows, |dirty| {
+            black_box(parse_common(dirty).value);
+        });
+        let mut out = String::new();
+        let single_pass = throughput(&rows, |dirty| {
+            fix_common_into(dirty, &mut out);
+            black_box(&out);
+        });
+        println!(
+            "{:<10} {:>16.0} {:>16.0} {:>7.2}x",
+            name,
+            all_steps,
+            single_pass,
+            single_pass / all_steps
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Functions to split combined "Artist - Title" strings.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{fix_common, is_inside_brackets};
+
+lazy_static! {
+    // A hyphen or tilde only counts as a separator when it is surrounded by
+    // whitespace, so that names like "Jay-Z" are not split. En and em dashes
+    // are rarely part of a name, so surrounding whitespace is optional.
+    static ref ARTIST_TITLE_SEPARATOR_REGEX: Regex =
+        Regex::new(r"[[:space:]]+[-~][[:space:]]+|[[:space:]]*[–—][[:space:]]*").unwrap();
+}
+
+/// An artist and title that were split out of a single string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArtistTitle {
+    /// The cleaned artist part, everything before the separator.
+    pub artist: String,
+    /// The cleaned title part, everything after the separator.
+    pub title: String,
+    /// `true` if the string contained more than one possible separator.
+    ///
+    /// The first separator is always used to split the string, so
+    /// `"A - B - C"` splits into the artist `"A"` and the title `"B - C"`.
+    pub ambiguous: bool,
+}
+
+/// Split a string such as `Artist - Title` into its artist and title.
+///
+/// The following separators are recognized:
+/// - ` - ` (a hyphen surrounded by whitespace)
+/// - ` ~ ` (a tilde surrounded by whitespace)
+/// - `–` and `—` (en and em dashes)
+///
+/// Separators inside parentheses or brackets are ignored. Both parts are
+/// cleaned with [`fix_common`]. Returns `None` if there is no separator or
+/// if either part would be empty.
+///
+pub fn split_artist_and_title(dirty: &str) -> Option<ArtistTitle> {
+    let separators: Vec<_> = ARTIST_TITLE_SEPARATOR_REGEX
+        .find_iter(dirty)
+        .filter(|m| !is_inside_brackets(dirty, m.start()))
+        .collect();
+    let first = separators.first()?;
+    let artist = fix_common(&dirty[..first.start()]);
+    let title = fix_common(&dirty[first.end()..]);
+    if artist.is_empty() || title.is_empty() {
+        return None;
+    }
+    Some(ArtistTitle {
+        artist,
+        title,
+        ambiguous: separators.len() > 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::artist_title::*;
+
+    #[test]
+    fn split_artist_and_title_1() {
+        let actual =
+            split_artist_and_title("Tyler, The Creator - IGOR (2019) Mp3 (320 kbps)").unwrap();
+        assert_eq!(actual.artist, "Tyler, The Creator");
+        assert_eq!(actual.title, "IGOR");
+        assert!(!actual.ambiguous);
+    }
+    #[test]
+    fn split_artist_and_title_2() {
+        let actual =
+            split_artist_and_title("Jay-Z – Empire State of Mind (Live - Acoustic)").unwrap();
+        assert_eq!(actual.artist, "Jay-Z");
+        assert_eq!(actual.title, "Empire State of Mind (Live - Acoustic)");
+        assert!(!actual.ambiguous);
+    }
+    #[test]
+    fn split_artist_and_title_3() {
+        let actual = split_artist_and_title("Artist ~ Album - Title").unwrap();
+        assert_eq!(actual.artist, "Artist");
+        assert_eq!(actual.title, "Album - Title");
+        assert!(actual.ambiguous);
+    }
+    #[test]
+    fn split_artist_and_title_4() {
+        assert_eq!(split_artist_and_title("Just A Title"), None);
+        assert_eq!(split_artist_and_title(" - Title"), None);
+    }
+}
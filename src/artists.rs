@@ -0,0 +1,283 @@
+//! Functions to split a string that credits several artists.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::names_regex_with;
+
+/// Artist names that contain one of our separators but must never be split.
+///
+/// Names are matched case-insensitively, and ` & `, ` and ` and ` + ` are
+/// interchangeable in them. [`crate::title_case`] also restores their casing.
+pub(crate) const KNOWN_ARTISTS: &[&str] = &[
+    "AC/DC",
+    "Above & Beyond",
+    "Angus & Julia Stone",
+    "Belle and Sebastian",
+    "Blood, Sweat & Tears",
+    "Bob Marley & the Wailers",
+    "Brooks & Dunn",
+    "Captain & Tennille",
+    "Chase & Status",
+    "Coheed and Cambria",
+    "Crosby, Stills & Nash",
+    "Crosby, Stills, Nash & Young",
+    "Earth, Wind & Fire",
+    "Echo & the Bunnymen",
+    "Emerson, Lake & Palmer",
+    "Florence and the Machine",
+    "Hall & Oates",
+    "Hootie & the Blowfish",
+    "Huey Lewis and the News",
+    "Ike & Tina Turner",
+    "Iron & Wine",
+    "Kool & the Gang",
+    "Marina and the Diamonds",
+    "Matt and Kim",
+    "Mumford & Sons",
+    "Nick Cave and the Bad Seeds",
+    "Of Monsters and Men",
+    "Peter, Paul and Mary",
+    "Sam & Dave",
+    "Simon & Garfunkel",
+    "Siouxsie and the Banshees",
+    "Sly & the Family Stone",
+    "Sonny & Cher",
+    "Tegan and Sara",
+    "Tom Petty and the Heartbreakers",
+    "Tyler, The Creator",
+    "Years & Years",
+];
+
+lazy_static! {
+    // The "x" collaboration separator is deliberately case-sensitive so that
+    // names such as "Lil Nas X" are left alone.
+    static ref ARTIST_SEPARATOR_REGEX: Regex = Regex::new(
+        r"[[:space:]]*(?:,|&|/|;|[[:space:]]+(?i:and|vs\.?)[[:space:]]+|[[:space:]]+[x×][[:space:]]+)[[:space:]]*"
+    ).unwrap();
+    static ref DEFAULT_ARTIST_SPLITTER: ArtistSplitter = ArtistSplitter::default();
+    pub(crate) static ref CONJUNCTION_REGEX: Regex = Regex::new(&format!("(?i){}", CONJUNCTION)).unwrap();
+}
+
+/// The ways of joining the last words of a name, as in `Earth, Wind & Fire`,
+/// `Earth, Wind and Fire` or `Florence + the Machine`.
+const CONJUNCTION: &str = r"[[:space:]]+(?:&|and|\+)[[:space:]]+";
+
+/// The regex that matches a known name, with any of its conjunctions.
+pub(crate) fn known_artist_pattern(name: &str) -> String {
+    CONJUNCTION_REGEX
+        .split(name)
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(CONJUNCTION)
+}
+
+/// Splits strings that credit several artists, without splitting the
+/// known names that contain a separator.
+///
+/// The built-in known names, such as `Tyler, The Creator` or
+/// `Earth, Wind & Fire`, can be extended with [`ArtistSplitter::protect`].
+/// [`split_artists`] uses the built-in names.
+///
+/// With the `serde` feature, only the `known` names are serialized, and a
+/// missing `known` field deserializes to the built-in names.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "ArtistSplitterFields", into = "ArtistSplitterFields")
+)]
+pub struct ArtistSplitter {
+    known: Vec<String>,
+    known_regex: Regex,
+}
+
+/// The serialized fields of an [`ArtistSplitter`], without its regex.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ArtistSplitterFields {
+    known: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl Default for ArtistSplitterFields {
+    fn default() -> Self {
+        ArtistSplitter::default().into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ArtistSplitterFields> for ArtistSplitter {
+    fn from(fields: ArtistSplitterFields) -> Self {
+        ArtistSplitter::with_known(fields.known)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ArtistSplitter> for ArtistSplitterFields {
+    fn from(splitter: ArtistSplitter) -> Self {
+        ArtistSplitterFields {
+            known: splitter.known,
+        }
+    }
+}
+
+impl Default for ArtistSplitter {
+    fn default() -> Self {
+        ArtistSplitter::with_known(KNOWN_ARTISTS.iter().map(|name| name.to_string()).collect())
+    }
+}
+
+impl ArtistSplitter {
+    fn with_known(known: Vec<String>) -> Self {
+        ArtistSplitter {
+            known_regex: names_regex_with(&known, known_artist_pattern),
+            known,
+        }
+    }
+
+    /// Never split `name`, which is matched case-insensitively, and with
+    /// ` & `, ` and ` or ` + ` between its words.
+    pub fn protect(mut self, name: &str) -> Self {
+        self.known.push(name.trim().to_string());
+        self.known_regex = names_regex_with(&self.known, known_artist_pattern);
+        self
+    }
+
+    /// The names that are never split.
+    pub fn known_artists(&self) -> &[String] {
+        &self.known
+    }
+
+    /// Split `artists` into individual names; see [`split_artists`].
+    pub fn split(&self, artists: &str) -> Vec<String> {
+        let protected: Vec<(usize, usize)> = self
+            .known_regex
+            .find_iter(artists)
+            // With no known names, the regex matches the empty string.
+            .filter(|m| !m.as_str().is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        let overlaps_protected =
+            |start: usize, end: usize| protected.iter().any(|&(s, e)| start < e && s < end);
+
+        let mut names = Vec::new();
+        let mut last = 0;
+        for separator in ARTIST_SEPARATOR_REGEX.find_iter(artists) {
+            if overlaps_protected(separator.start(), separator.end()) {
+                continue;
+            }
+            names.push(&artists[last..separator.start()]);
+            last = separator.end();
+        }
+        names.push(&artists[last..]);
+
+        names
+            .into_iter()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Split a string that credits one or more artists into individual names.
+///
+/// The string is split on the separators commonly used to join artists:
+/// - `,`, `&`, `/` and `;`
+/// - ` and `, ` vs `, ` vs. `
+/// - ` x ` (lowercase only, as in `Artist x Artist`)
+///
+/// Well-known names that contain a separator, such as `Tyler, The Creator`
+/// or `Earth, Wind & Fire`, are kept intact. Empty entries are dropped.
+/// To keep other names intact, use an [`ArtistSplitter`].
+///
+pub fn split_artists(artists: &str) -> Vec<String> {
+    DEFAULT_ARTIST_SPLITTER.split(artists)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::artists::*;
+    use crate::fix_artists_string;
+
+    #[test]
+    fn split_artists_1() {
+        let actual = split_artists("Drake, Future & Young Thug");
+        assert_eq!(actual, vec!["Drake", "Future", "Young Thug"]);
+    }
+    #[test]
+    fn split_artists_2() {
+        let actual = split_artists("Tyler, The Creator & Earth, Wind & Fire");
+        assert_eq!(actual, vec!["Tyler, The Creator", "Earth, Wind & Fire"]);
+    }
+    #[test]
+    fn split_artists_3() {
+        let actual = split_artists("Skrillex x Diplo vs. Lil Nas X/Kanye West; Jay-Z and Beyoncé");
+        assert_eq!(
+            actual,
+            vec!["Skrillex", "Diplo", "Lil Nas X", "Kanye West", "Jay-Z", "Beyoncé"]
+        );
+    }
+    #[test]
+    fn split_artists_4() {
+        let actual = split_artists("AC/DC");
+        assert_eq!(actual, vec!["AC/DC"]);
+    }
+    #[test]
+    fn split_artists_5() {
+        let actual = split_artists("Of Monsters and Men, Iron & Wine & Chase & Status");
+        assert_eq!(
+            actual,
+            vec!["Of Monsters and Men", "Iron & Wine", "Chase & Status"]
+        );
+        let actual = split_artists("Hootie & the Blowfish");
+        assert_eq!(actual, vec!["Hootie & the Blowfish"]);
+    }
+    #[test]
+    fn split_artists_6() {
+        for (one, other) in &[
+            ("Earth, Wind & Fire", "Earth, Wind and Fire"),
+            ("Simon & Garfunkel", "Simon and Garfunkel"),
+            ("Hall & Oates", "Hall and Oates"),
+            ("Mumford & Sons", "Mumford and Sons"),
+            ("Florence and the Machine", "Florence + the Machine"),
+            ("Bob Marley & the Wailers", "Bob Marley and the Wailers"),
+        ] {
+            assert_eq!(split_artists(one), vec![*one]);
+            assert_eq!(split_artists(other), vec![*other]);
+        }
+        assert_eq!(
+            fix_artists_string("Earth, Wind and Fire"),
+            vec!["Earth, Wind and Fire"]
+        );
+    }
+    #[test]
+    fn artist_splitter_1() {
+        let splitter = ArtistSplitter::default().protect(" Brother & Sister ");
+        assert_eq!(splitter.split("brother & sister"), vec!["brother & sister"]);
+        assert_eq!(splitter.split("Drake & Future"), vec!["Drake", "Future"]);
+        assert!(splitter
+            .known_artists()
+            .contains(&"Brother & Sister".to_string()));
+        assert_eq!(split_artists("Brother & Sister"), vec!["Brother", "Sister"]);
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn artist_splitter_serde_1() {
+        let splitter: ArtistSplitter = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            splitter.known_artists(),
+            ArtistSplitter::default().known_artists()
+        );
+        let splitter: ArtistSplitter =
+            serde_json::from_str(r#"{"known":["Brother & Sister"]}"#).unwrap();
+        assert_eq!(splitter.split("Brother and Sister"), vec!["Brother and Sister"]);
+        assert_eq!(splitter.split("Hall & Oates"), vec!["Hall", "Oates"]);
+        let json = serde_json::to_string(&splitter).unwrap();
+        assert_eq!(json, r#"{"known":["Brother & Sister"]}"#);
+        let splitter: ArtistSplitter = serde_json::from_str(r#"{"known":[]}"#).unwrap();
+        assert_eq!(splitter.split("Drake & Future"), vec!["Drake", "Future"]);
+    }
+}
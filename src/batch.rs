@@ -0,0 +1,94 @@
+//! Cleaning of many strings at once, in parallel with the `rayon` feature.
+//!
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// The number of strings that [`clean_batch_iter`] reads ahead of its output.
+const CHUNK_SIZE: usize = 4096;
+
+/// Clean every string of `dirty` with `clean`, such as [`crate::fix_common`].
+///
+/// The cleaned strings are returned in the order of `dirty`, and are the
+/// same as `dirty.iter().map(|s| clean(s.as_ref()))` would return. With the
+/// `rayon` feature, the strings are cleaned in parallel on the global
+/// rayon thread pool.
+///
+/// ```
+/// use music_metadata_cleaner::{clean_batch, fix_common};
+///
+/// let cleaned = clean_batch(&["IGOR (2019)", "Flower Boy [Mp3]"], fix_common);
+/// assert_eq!(cleaned, vec!["IGOR", "Flower Boy"]);
+/// ```
+///
+pub fn clean_batch<S, F>(dirty: &[S], clean: F) -> Vec<String>
+where
+    S: AsRef<str> + Sync,
+    F: Fn(&str) -> String + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        dirty.par_iter().map(|s| clean(s.as_ref())).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        dirty.iter().map(|s| clean(s.as_ref())).collect()
+    }
+}
+
+/// Like [`clean_batch`], but lazily cleans the strings of an iterator, such
+/// as the lines of a file that does not fit in memory.
+///
+/// The strings are read and cleaned in chunks, so the cleaned strings come
+/// out in the order of `dirty`, and at most a few thousand strings are held
+/// in memory at once.
+///
+pub fn clean_batch_iter<I, F>(dirty: I, clean: F) -> impl Iterator<Item = String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str> + Sync,
+    F: Fn(&str) -> String + Sync + Send,
+{
+    let mut dirty = dirty.into_iter();
+    std::iter::from_fn(move || {
+        let chunk: Vec<I::Item> = dirty.by_ref().take(CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(clean_batch(&chunk, &clean))
+        }
+    })
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::batch::*;
+    use crate::{fix_album_title, fix_common};
+
+    const DIRTY: &[&str] = &[
+        "Tyler, The Creator - IGOR (2019) [Mp3] (320 kbps)",
+        "  Blonde   (2016)",
+        "Flower Boy ()",
+        "",
+        "Nocturnes, Opus 27 (Vinyl Rip) [V0]",
+    ];
+
+    #[test]
+    fn clean_batch_1() {
+        let expected: Vec<String> = DIRTY.iter().map(|s| fix_common(s)).collect();
+        assert_eq!(clean_batch(DIRTY, fix_common), expected);
+        let owned: Vec<String> = DIRTY.iter().map(|s| s.to_string()).collect();
+        assert_eq!(clean_batch(&owned, fix_common), expected);
+    }
+    #[test]
+    fn clean_batch_iter_1() {
+        let dirty = DIRTY.iter().cycle().take(CHUNK_SIZE * 2 + 3);
+        let expected: Vec<String> = dirty.clone().map(|s| fix_album_title(s)).collect();
+        let actual: Vec<String> = clean_batch_iter(dirty, fix_album_title).collect();
+        assert_eq!(actual, expected);
+        assert_eq!(
+            clean_batch_iter(Vec::<String>::new(), fix_common).count(),
+            0
+        );
+    }
+}
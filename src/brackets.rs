@@ -0,0 +1,256 @@
+//! Bracket-aware removal of annotations.
+//!
+use std::borrow::Cow;
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::explain::apply_edits;
+use crate::Edit;
+
+/// The pairs of brackets that can enclose an annotation.
+pub(crate) const BRACKETS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}'), ('【', '】')];
+
+/// Characters that may separate the labels inside a bracket group, as in
+/// `[FLAC, 24bit]` or `(Mp3 / 320)`.
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | ';' | '/' | '|' | '+' | '&' | '-' | '–' | '—')
+}
+
+/// A balanced pair of brackets in a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BracketGroup {
+    /// The byte range of the group, including both brackets.
+    pub(crate) span: Range<usize>,
+    /// The byte range between the brackets.
+    pub(crate) content: Range<usize>,
+}
+
+/// Returns every balanced bracket group in `dirty`, in the order that the
+/// groups close, so that nested groups come before the groups around them.
+///
+/// Brackets without a partner are ignored. A closing bracket closes the
+/// nearest open bracket of the same kind, and any unclosed brackets inside
+/// it are ignored.
+pub(crate) fn bracket_groups(dirty: &str) -> Vec<BracketGroup> {
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut groups = Vec::new();
+    for (index, c) in dirty.char_indices() {
+        if let Some(&(opening, _)) = BRACKETS.iter().find(|(opening, _)| *opening == c) {
+            open.push((opening, index));
+        } else if let Some(&(opening, _)) = BRACKETS.iter().find(|(_, closing)| *closing == c) {
+            if let Some(depth) = open.iter().rposition(|(o, _)| *o == opening) {
+                let start = open[depth].1;
+                open.truncate(depth);
+                groups.push(BracketGroup {
+                    span: start..index + c.len_utf8(),
+                    content: start + opening.len_utf8()..index,
+                });
+            }
+        }
+    }
+    groups
+}
+
+/// Returns `true` if `content` is nothing but matches of `labels` and separators.
+pub(crate) fn is_noise(content: &str, labels: &[&Regex]) -> bool {
+    let mut rest = content.to_string();
+    for regex in labels {
+        rest = regex.replace_all(&rest, " ").into_owned();
+    }
+    rest.chars().all(is_separator)
+}
+
+/// Returns the edits that remove annotations from `dirty`.
+///
+/// A bracket group is removed as a whole when its content, once the
+/// nested groups that were removed are left out, is made only of matches
+/// of `anywhere` or `bracketed` and separators. Empty groups are noise too,
+/// so with no labels at all this removes only the empty brackets.
+///
+/// Matches of `anywhere` outside of the removed groups are removed on their
+/// own, while matches of `bracketed` are only removed with their group.
+pub(crate) fn annotation_edits(
+    dirty: &str,
+    anywhere: &[&Regex],
+    bracketed: &[&Regex],
+) -> Vec<Edit> {
+    let labels: Vec<&Regex> = anywhere.iter().chain(bracketed).copied().collect();
+    let mut removed: Vec<Range<usize>> = Vec::new();
+    for group in bracket_groups(dirty) {
+        let inner: Vec<Range<usize>> = removed
+            .iter()
+            .filter(|span| group.content.start <= span.start && span.end <= group.content.end)
+            .cloned()
+            .collect();
+        let mut content = String::new();
+        let mut last = group.content.start;
+        for span in &inner {
+            content.push_str(&dirty[last..span.start]);
+            content.push(' ');
+            last = span.end;
+        }
+        content.push_str(&dirty[last..group.content.end]);
+        if is_noise(&content, &labels) {
+            removed.retain(|span| !inner.contains(span));
+            removed.push(group.span);
+        }
+    }
+    for regex in anywhere {
+        for m in regex.find_iter(dirty) {
+            let overlaps = removed
+                .iter()
+                .any(|span| span.start < m.end() && m.start() < span.end);
+            if !overlaps {
+                removed.push(m.range());
+            }
+        }
+    }
+    removed.sort_by_key(|span| span.start);
+    removed
+        .into_iter()
+        .map(|span| Edit {
+            span,
+            replacement: " ".to_string(),
+        })
+        .collect()
+}
+
+/// Applies the edits from [`annotation_edits`] to `dirty`.
+pub(crate) fn remove_annotations<'a>(
+    dirty: &'a str,
+    anywhere: &[&Regex],
+    bracketed: &[&Regex],
+) -> Cow<'a, str> {
+    let edits = annotation_edits(dirty, anywhere, bracketed);
+    if edits.is_empty() {
+        Cow::Borrowed(dirty)
+    } else {
+        Cow::Owned(apply_edits(dirty, &edits))
+    }
+}
+
+/// Returns the edits that remove the debris that removing annotations
+/// leaves around brackets:
+/// - unmatched brackets with only separators and other unmatched brackets
+///   between them and the end of the string or another bracket, as in
+///   `Title (` or `Title ()]`, or between them and an unmatched bracket that
+///   faces them, as in `Title [ ) Remix`
+/// - whitespace just inside a bracket group, as in `Title ( Remix)`
+///
+/// Empty bracket groups are not debris; see [`annotation_edits`].
+pub(crate) fn bracket_debris_edits(dirty: &str) -> Vec<Edit> {
+    let groups = bracket_groups(dirty);
+    let is_opening = |c: char| BRACKETS.iter().any(|&(opening, _)| c == opening);
+    let is_closing = |c: char| BRACKETS.iter().any(|&(_, closing)| c == closing);
+    let unmatched = |index: usize, c: char| {
+        (is_opening(c) || is_closing(c))
+            && !groups
+                .iter()
+                .any(|group| index == group.span.start || index == group.content.end)
+    };
+    let chars: Vec<(usize, char)> = dirty.char_indices().collect();
+    let is_filler = |&&(index, c): &&(usize, char)| is_separator(c) || unmatched(index, c);
+
+    let mut edits = Vec::new();
+    for (position, &(index, c)) in chars.iter().enumerate() {
+        if !unmatched(index, c) {
+            continue;
+        }
+        let debris = if is_opening(c) {
+            let after = &chars[position + 1..];
+            let run = after.iter().take_while(is_filler).count();
+            after
+                .get(run)
+                .is_none_or(|&(_, next)| is_opening(next) || is_closing(next))
+                || after[..run]
+                    .iter()
+                    .any(|&(i, c)| is_closing(c) && unmatched(i, c))
+        } else {
+            let before = &chars[..position];
+            let run = before.iter().rev().take_while(is_filler).count();
+            let rest = before.len() - run;
+            rest == 0
+                || is_opening(before[rest - 1].1)
+                || is_closing(before[rest - 1].1)
+                || before[rest..]
+                    .iter()
+                    .any(|&(i, c)| is_opening(c) && unmatched(i, c))
+        };
+        if debris {
+            edits.push(Edit {
+                span: index..index + c.len_utf8(),
+                replacement: " ".to_string(),
+            });
+        }
+    }
+
+    for group in &groups {
+        let content = &dirty[group.content.clone()];
+        if content.trim().is_empty() {
+            continue;
+        }
+        let leading = content.len() - content.trim_start().len();
+        let trailing = content.len() - content.trim_end().len();
+        for span in [
+            group.content.start..group.content.start + leading,
+            group.content.end - trailing..group.content.end,
+        ] {
+            if !span.is_empty() {
+                edits.push(Edit {
+                    span,
+                    replacement: String::new(),
+                });
+            }
+        }
+    }
+    edits.sort_by_key(|edit| edit.span.start);
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brackets::*;
+
+    fn remove(dirty: &str) -> String {
+        let label = Regex::new(r"(?i)\bmp3\b").unwrap();
+        crate::remove_empty_brackets(&remove_annotations(dirty, &[&label], &[]))
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn bracket_groups_1() {
+        let spans: Vec<_> = bracket_groups("a [b (c) {d}] 【e】")
+            .into_iter()
+            .map(|group| group.span)
+            .collect();
+        assert_eq!(spans, vec![5..8, 9..12, 2..13, 14..21]);
+    }
+    #[test]
+    fn bracket_groups_2() {
+        let spans: Vec<_> = bracket_groups("a (b [c) d] e)")
+            .into_iter()
+            .map(|group| group.span)
+            .collect();
+        assert_eq!(spans, vec![2..8]);
+    }
+    #[test]
+    fn remove_annotations_1() {
+        assert_eq!(remove("Title [Mp3]"), "Title");
+        assert_eq!(remove("Title (Mp3, [MP3]) {}"), "Title");
+        assert_eq!(remove("Title 【 mp3 】"), "Title");
+        assert_eq!(remove("Title Mp3 ( )"), "Title");
+    }
+    #[test]
+    fn remove_annotations_2() {
+        assert_eq!(remove("Title (Mp3 Remix)"), "Title (Remix)");
+        assert_eq!(remove("Title (Live [Mp3])"), "Title (Live)");
+        assert_eq!(remove("Title (Mp3"), "Title");
+        assert_eq!(remove("Title [Mp3) Remix"), "Title Remix");
+        assert_eq!(remove("Title ([Mp3)]"), "Title");
+        assert_eq!(remove("Title (Live"), "Title (Live");
+        assert_eq!(remove("Title :) ( Remix )"), "Title :) (Remix)");
+    }
+}
@@ -0,0 +1,240 @@
+//! Title-casing for strings that were uploaded in noisy casing.
+//!
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::artists::{known_artist_pattern, CONJUNCTION_REGEX, KNOWN_ARTISTS};
+use crate::{names_regex, names_regex_with};
+
+/// Words that stay lowercase in a title, unless they start or end it.
+///
+/// This includes `x`, as in `Artist x Artist`, so that it is not
+/// mistaken for a Roman numeral.
+const SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "en", "for", "from", "in", "into", "n", "nor", "of",
+    "on", "or", "the", "to", "vs", "via", "with", "x",
+];
+
+/// Names and acronyms whose casing is always restored, even when the
+/// input was all lowercase or all uppercase. Names are matched
+/// case-insensitively.
+///
+/// The known artist names that are never split, such as
+/// `Tyler, The Creator`, are restored too.
+const PROTECTED_CASINGS: &[&str] = &[
+    "a-ha",
+    "ABBA",
+    "AC/DC",
+    "BTS",
+    "blink-182",
+    "deadmau5",
+    "DJ",
+    "EP",
+    "IGOR",
+    "k.d. lang",
+    "LP",
+    "MC",
+    "MF DOOM",
+    "MGMT",
+    "NYC",
+    "OK",
+    "R&B",
+    "TLC",
+    "UK",
+    "USA",
+    "will.i.am",
+];
+
+lazy_static! {
+    static ref WORD_REGEX: Regex = Regex::new(r"[^[:space:]]+").unwrap();
+    static ref ROMAN_NUMERAL_REGEX: Regex = Regex::new(r"^(?i)x{0,3}(?:ix|iv|v?i{0,3})$").unwrap();
+    static ref PROTECTED_CASINGS_REGEX: Regex = names_regex(PROTECTED_CASINGS);
+    static ref KNOWN_ARTISTS_REGEX: Regex = names_regex_with(KNOWN_ARTISTS, known_artist_pattern);
+}
+
+/// Returns `true` if a word looks like `aLtErNaTiNg` case rather than
+/// deliberate mixed case such as `McDonald` or `iPhone`.
+fn is_alternating_case(word: &str) -> bool {
+    let letters: Vec<bool> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .map(char::is_uppercase)
+        .collect();
+    let transitions = letters.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let starts_lowercase = letters.first() == Some(&false);
+    transitions >= 4 || (starts_lowercase && transitions >= 3)
+}
+
+/// Uppercase the first alphabetic character of `part`, which must already be lowercase.
+fn capitalize(part: &str) -> String {
+    let mut capitalized = String::with_capacity(part.len());
+    let mut done = false;
+    for (index, c) in part.char_indices() {
+        if !done && c.is_alphabetic() {
+            capitalized.extend(c.to_uppercase());
+            // Irish names such as `O'Connor`.
+            done = !(c == 'o' && part[index..].starts_with("o'") && part.len() > index + 2);
+        } else {
+            capitalized.push(c);
+        }
+    }
+    capitalized
+}
+
+/// Returns the known artist name that `matched` spells, keeping the
+/// ` & `, ` and ` or ` + ` that `matched` uses between its words.
+fn restore_known_artist(matched: &str) -> String {
+    let key = |name: &str| {
+        CONJUNCTION_REGEX
+            .replace_all(&name.to_lowercase(), " & ")
+            .into_owned()
+    };
+    let name = match KNOWN_ARTISTS.iter().find(|name| key(name) == key(matched)) {
+        Some(name) => name,
+        None => return matched.to_string(),
+    };
+    let mut conjunctions = CONJUNCTION_REGEX.find_iter(matched);
+    let mut restored = String::with_capacity(matched.len());
+    for (index, part) in CONJUNCTION_REGEX.split(name).enumerate() {
+        if index > 0 {
+            restored.push_str(conjunctions.next().map_or(" & ", |c| c.as_str()));
+        }
+        restored.push_str(part);
+    }
+    restored
+}
+
+/// Title-case a single word.
+fn case_word(word: &str, lowercase_small_words: bool) -> String {
+    let lower = word.to_lowercase();
+    let core = lower.trim_matches(|c: char| !c.is_alphanumeric());
+    if core.is_empty() {
+        return word.to_string();
+    }
+    if lowercase_small_words && SMALL_WORDS.contains(&core) {
+        return lower;
+    }
+    if ROMAN_NUMERAL_REGEX.is_match(core) {
+        return lower.replace(core, &core.to_uppercase());
+    }
+    lower
+        .split('-')
+        .map(capitalize)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Title-case a string that was written in all caps, all lowercase,
+/// or alternating case.
+///
+/// Strings that already use deliberate mixed casing, such as
+/// `Tyler, The Creator - IGOR`, are returned unchanged. Otherwise every
+/// word is capitalized, except for:
+/// - small words such as `the`, `of` or `and`, unless they start or end the
+///   title or follow a `:`, `-` or an opening bracket
+/// - Roman numerals, which are uppercased, as in `Part II`
+/// - protected names and acronyms, such as `MF DOOM`, `deadmau5` or
+///   `blink-182`, and known artists such as `Tyler, The Creator`, which
+///   get their canonical casing
+///
+/// This step is not part of [`crate::fix_common`], because some artists
+/// deliberately use unusual casing. Add [`crate::Step::TitleCase`] to the
+/// [`crate::Pipeline`] of the fields that need it.
+///
+pub fn title_case(dirty: &str) -> String {
+    let has_upper = dirty.chars().any(char::is_uppercase);
+    let has_lower = dirty.chars().any(char::is_lowercase);
+    let noisy = !(has_upper && has_lower)
+        || WORD_REGEX
+            .find_iter(dirty)
+            .any(|word| is_alternating_case(word.as_str()));
+    if !noisy {
+        return dirty.to_string();
+    }
+
+    let words: Vec<_> = WORD_REGEX.find_iter(dirty).collect();
+    let mut cased = String::with_capacity(dirty.len());
+    let mut last = 0;
+    for (index, word) in words.iter().enumerate() {
+        let text = word.as_str();
+        let starts_phrase = index == 0
+            || text.starts_with(['(', '['])
+            || words[index - 1].as_str().ends_with(':')
+            || matches!(words[index - 1].as_str(), "-" | "–" | "—");
+        let ends_phrase = index + 1 == words.len() || text.ends_with([')', ']']);
+        cased.push_str(&dirty[last..word.start()]);
+        cased.push_str(&case_word(text, !starts_phrase && !ends_phrase));
+        last = word.end();
+    }
+    cased.push_str(&dirty[last..]);
+
+    let cased =
+        KNOWN_ARTISTS_REGEX.replace_all(&cased, |caps: &Captures| restore_known_artist(&caps[0]));
+    PROTECTED_CASINGS_REGEX
+        .replace_all(&cased, |caps: &Captures| {
+            let matched = caps[0].to_lowercase();
+            PROTECTED_CASINGS
+                .iter()
+                .find(|name| name.to_lowercase() == matched)
+                .map_or(caps[0].to_string(), |name| name.to_string())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::casing::*;
+
+    #[test]
+    fn title_case_1() {
+        assert_eq!(
+            title_case("THE DARK SIDE OF THE MOON"),
+            "The Dark Side of the Moon"
+        );
+        assert_eq!(title_case("a night at the opera"), "A Night at the Opera");
+        assert_eq!(title_case("sOmEtHiNg iN tHe wAy"), "Something in the Way");
+    }
+    #[test]
+    fn title_case_2() {
+        assert_eq!(
+            title_case("ROCKY II: THE EYE OF THE TIGER"),
+            "Rocky II: The Eye of the Tiger"
+        );
+        assert_eq!(
+            title_case("don't stop me now (o'connor remix)"),
+            "Don't Stop Me Now (O'Connor Remix)"
+        );
+        assert_eq!(
+            title_case("ROCK 'N' ROLL - THE BEST"),
+            "Rock 'n' Roll - The Best"
+        );
+    }
+    #[test]
+    fn title_case_3() {
+        assert_eq!(title_case("mf doom - all caps"), "MF DOOM - All Caps");
+        assert_eq!(title_case("DEADMAU5 & BLINK-182"), "deadmau5 & blink-182");
+    }
+    #[test]
+    fn title_case_4() {
+        assert_eq!(
+            title_case("Tyler, The Creator - IGOR"),
+            "Tyler, The Creator - IGOR"
+        );
+        assert_eq!(title_case("McDonald's iPhone"), "McDonald's iPhone");
+    }
+    #[test]
+    fn title_case_5() {
+        assert_eq!(
+            title_case("TYLER, THE CREATOR - IGOR"),
+            "Tyler, The Creator - IGOR"
+        );
+        assert_eq!(
+            title_case("earth, wind and fire - september"),
+            "Earth, Wind and Fire - September"
+        );
+        assert_eq!(
+            title_case("FLORENCE + THE MACHINE - DOG DAYS"),
+            "Florence + the Machine - Dog Days"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Functions to extract featured artist and producer credits from track titles.
+//!
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::{fix_artists_string, fix_track_title, is_inside_brackets, normalize_unicode};
+
+lazy_static! {
+    // A whole parenthesized or bracketed credit, such as `(feat. Drake)`
+    // or `[Prod. by Metro Boomin]`. Inside brackets we can afford to also
+    // accept `with`, which would be too ambiguous in a bare title.
+    static ref BRACKETED_CREDIT_REGEX: Regex = Regex::new(
+        r"(?i)[\(\[][[:space:]]*(feat\.?|ft\.?|featuring|with|prod\.?[[:space:]]+by|prod\.?|produced[[:space:]]+by)[[:space:]]+([^\(\)\[\]]+?)[[:space:]]*[\)\]]"
+    ).unwrap();
+    // A credit keyword outside of brackets, optionally introduced by a dash,
+    // such as ` ft. X` or ` - prod. by Y`. A bare `with` is only a credit
+    // after a dash, as in ` - with Z`.
+    static ref BARE_CREDIT_REGEX: Regex = Regex::new(
+        r"(?i)(?:^|[[:space:]])(?:(?:-[[:space:]]*)?(feat\.?|ft\.?|featuring|prod\.[[:space:]]+by|prod\.|produced[[:space:]]+by)|-[[:space:]]*(with))[[:space:]]+"
+    ).unwrap();
+}
+
+/// A track title with its featured artist and producer credits split out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackCredits {
+    /// The cleaned track title, without any credits.
+    pub title: String,
+    /// Artists credited with `feat.`, `ft.`, `featuring` or `with`.
+    pub featured_artists: Vec<String>,
+    /// Artists credited with `prod.`, `prod. by` or `produced by`.
+    pub producers: Vec<String>,
+}
+
+impl TrackCredits {
+    fn add_credit(&mut self, keyword: &str, names: &str) {
+        let names = names.trim_end_matches(|c: char| c == '-' || c.is_whitespace());
+        let list = if keyword.to_lowercase().starts_with("prod") {
+            &mut self.producers
+        } else {
+            &mut self.featured_artists
+        };
+        for name in fix_artists_string(names) {
+            if !list.contains(&name) {
+                list.push(name);
+            }
+        }
+    }
+}
+
+/// Returns the position of the first credit keyword that is not inside
+/// brackets and follows some title text, so that titles such as
+/// `Ft. Lauderdale` are kept.
+fn find_bare_credit(dirty: &str) -> Option<usize> {
+    BARE_CREDIT_REGEX
+        .find_iter(dirty)
+        .map(|m| m.start())
+        .find(|&start| !is_inside_brackets(dirty, start) && !dirty[..start].trim().is_empty())
+}
+
+/// Split a raw track title into the title and its credited artists.
+///
+/// Credits are recognized in these forms:
+/// - `Title (feat. Artist)`, `Title [ft. Artist]`, `Title (with Artist)`
+/// - `Title (Prod. by Producer)`, `Title [prod. Producer]`
+/// - `Title ft. Artist & Artist`, `Title - feat. Artist`, `Title - with Artist`
+///
+/// Credited names are split with [`crate::split_artists`], and the
+/// remaining title is cleaned with [`fix_track_title`].
+///
+pub fn parse_track_credits(dirty: &str) -> TrackCredits {
+    let mut credits = TrackCredits::default();
+    let dirty = normalize_unicode(dirty);
+
+    let without_brackets = BRACKETED_CREDIT_REGEX.replace_all(&dirty, |caps: &Captures| {
+        credits.add_credit(&caps[1], &caps[2]);
+        " "
+    });
+
+    let mut title = without_brackets.to_string();
+    if let Some(start) = find_bare_credit(&title) {
+        // A bare credit runs until the next bracket or the end of the title,
+        // so that a trailing `(Remix)` stays with the title.
+        let end = title[start..]
+            .find(['(', '['])
+            .map_or(title.len(), |offset| start + offset);
+        let tail = &title[start..end];
+        let keywords: Vec<Captures> = BARE_CREDIT_REGEX.captures_iter(tail).collect();
+        for (index, caps) in keywords.iter().enumerate() {
+            let names_start = caps.get(0).unwrap().end();
+            let names_end = keywords
+                .get(index + 1)
+                .map_or(tail.len(), |next| next.get(0).unwrap().start());
+            let keyword = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+            credits.add_credit(keyword, &tail[names_start..names_end]);
+        }
+        title.replace_range(start..end, " ");
+    }
+
+    credits.title = fix_track_title(&title);
+    credits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::credits::*;
+
+    #[test]
+    fn parse_track_credits_1() {
+        let actual = parse_track_credits("Sicko Mode (feat. Drake)");
+        assert_eq!(actual.title, "Sicko Mode");
+        assert_eq!(actual.featured_artists, vec!["Drake"]);
+        assert!(actual.producers.is_empty());
+    }
+    #[test]
+    fn parse_track_credits_2() {
+        let actual = parse_track_credits("Song ft. X & Y [Prod. by Z]");
+        assert_eq!(actual.title, "Song");
+        assert_eq!(actual.featured_artists, vec!["X", "Y"]);
+        assert_eq!(actual.producers, vec!["Z"]);
+    }
+    #[test]
+    fn parse_track_credits_3() {
+        let actual = parse_track_credits("Song - feat. Jay-Z prod. Metro Boomin (Remix)");
+        assert_eq!(actual.title, "Song (Remix)");
+        assert_eq!(actual.featured_artists, vec!["Jay-Z"]);
+        assert_eq!(actual.producers, vec!["Metro Boomin"]);
+    }
+    #[test]
+    fn parse_track_credits_4() {
+        let actual = parse_track_credits("Dance with Me (with Tyler, The Creator)");
+        assert_eq!(actual.title, "Dance with Me");
+        assert_eq!(actual.featured_artists, vec!["Tyler, The Creator"]);
+    }
+    #[test]
+    fn parse_track_credits_5() {
+        let actual = parse_track_credits("Song - with Drake");
+        assert_eq!(actual.title, "Song");
+        assert_eq!(actual.featured_artists, vec!["Drake"]);
+        let actual = parse_track_credits("Stay with Me");
+        assert_eq!(actual.title, "Stay with Me");
+        assert!(actual.featured_artists.is_empty());
+    }
+    #[test]
+    fn parse_track_credits_6() {
+        let actual = parse_track_credits("Song （feat. Drake）");
+        assert_eq!(actual.title, "Song");
+        assert_eq!(actual.featured_artists, vec!["Drake"]);
+    }
+    #[test]
+    fn parse_track_credits_7() {
+        let actual = parse_track_credits("Ft. Lauderdale");
+        assert_eq!(actual.title, "Ft. Lauderdale");
+        assert!(actual.featured_artists.is_empty());
+        let actual = parse_track_credits("Featuring Ty Dolla Sign");
+        assert_eq!(actual.title, "Featuring Ty Dolla Sign");
+        assert!(actual.featured_artists.is_empty());
+        let actual = parse_track_credits("Ft. Lauderdale ft. Drake");
+        assert_eq!(actual.title, "Ft. Lauderdale");
+        assert_eq!(actual.featured_artists, vec!["Drake"]);
+    }
+}
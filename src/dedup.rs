@@ -0,0 +1,322 @@
+//! Grouping of the rows of a dataset that name the same track.
+//!
+use std::collections::HashMap;
+
+use crate::{edit_similarity, fix_common, fix_track_title, match_key};
+
+/// Blocks with more distinct titles than this are split by the prefixes
+/// of their titles, so that clustering does not take time quadratic in the
+/// catalog of a prolific artist.
+const MAX_BLOCK_LEN: usize = 1000;
+
+/// A distinct key in a block: its ID, its canonical title and the length
+/// of the title in characters.
+type Entry<'k> = (usize, &'k str, usize);
+
+/// A group of rows that name the same track.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cluster {
+    /// The [`crate::match_key`] of the representative spelling.
+    pub key: String,
+    /// The artists of the representative spelling, cleaned with [`crate::fix_common`].
+    pub artist: String,
+    /// The title of the representative spelling, cleaned with [`crate::fix_track_title`].
+    pub title: String,
+    /// The number of rows in the cluster.
+    pub size: usize,
+}
+
+/// The clusters found by [`Deduplicator::cluster`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clustering {
+    /// The cluster ID of every row, in the order of the rows. A cluster ID
+    /// is an index into `clusters`.
+    pub cluster_ids: Vec<usize>,
+    /// The clusters, in the order of their first row.
+    pub clusters: Vec<Cluster>,
+}
+
+/// How often a cleaned spelling appears in a cluster, and where it first appears.
+struct Spelling {
+    key: usize,
+    count: usize,
+    first_row: usize,
+}
+
+/// Spellings that are all lowercase or all uppercase are a worse choice
+/// than spellings in mixed case, such as `Beyoncé` over `BEYONCE`.
+fn is_mixed_case(artist: &str, title: &str) -> bool {
+    let text = format!("{}{}", artist, title);
+    text.chars().any(char::is_lowercase) && text.chars().any(char::is_uppercase)
+}
+
+/// A disjoint-set forest over the distinct keys.
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+/// Groups the (artist, title) rows of a dataset into clusters of likely
+/// duplicates.
+///
+/// Every row is reduced to its [`crate::match_key`], so rows whose keys are
+/// equal, such as `Beyoncé - Crazy In Love (feat. JAY-Z)` and
+/// `Beyonce & Jay-Z - crazy in love`, always share a cluster. Then rows
+/// are blocked by the artists of their key, and within a block, titles
+/// whose [`crate::edit_similarity`] is at least the threshold are merged,
+/// so that typos such as `Crazy in Lov` join their cluster as well.
+///
+/// Only distinct keys are compared, and only with keys of the same artists
+/// and of a similar length, so that millions of rows can be clustered in
+/// memory. Rows with different artists are never merged. An artist with
+/// more than a thousand distinct titles has their titles blocked again by
+/// their first characters, as many as it takes to get blocks of at most a
+/// thousand titles, so a typo in those first characters is not merged.
+/// Clustering thus takes at most `O(n · b · l²)` time for `n` distinct
+/// keys, blocks of `b ≤ 1000` titles and titles of `l` characters.
+///
+/// Each cluster is represented by its most common cleaned spelling. Ties
+/// go to spellings in mixed case, and then to the spelling seen first.
+///
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Deduplicator {
+    threshold: f64,
+}
+
+impl Default for Deduplicator {
+    fn default() -> Self {
+        Deduplicator { threshold: 0.9 }
+    }
+}
+
+impl Deduplicator {
+    /// Merge titles of the same artists whose [`crate::edit_similarity`] is
+    /// at least `threshold`, from `0.0` to `1.0`. The default is `0.9`; a
+    /// threshold of `1.0` only merges rows with equal keys.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Group `rows` of (artist, title) pairs into clusters of likely duplicates.
+    pub fn cluster<I, A, T>(&self, rows: I) -> Clustering
+    where
+        I: IntoIterator<Item = (A, T)>,
+        A: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let mut key_ids: HashMap<String, usize> = HashMap::new();
+        let mut keys: Vec<String> = Vec::new();
+        let mut row_keys: Vec<usize> = Vec::new();
+        let mut spellings: HashMap<(String, String), Spelling> = HashMap::new();
+        for (row, (artist, title)) in rows.into_iter().enumerate() {
+            let (artist, title) = (artist.as_ref(), title.as_ref());
+            let key = match_key(title, artist);
+            let key = *key_ids.entry(key).or_insert_with_key(|key| {
+                keys.push(key.clone());
+                keys.len() - 1
+            });
+            row_keys.push(key);
+            spellings
+                .entry((fix_common(artist), fix_track_title(title)))
+                .or_insert(Spelling {
+                    key,
+                    count: 0,
+                    first_row: row,
+                })
+                .count += 1;
+        }
+
+        let mut sets = UnionFind::new(keys.len());
+        for block in self.blocks(&keys).iter_mut() {
+            self.merge_block(block, &mut sets);
+        }
+
+        let mut clustering = Clustering::default();
+        let mut cluster_ids: HashMap<usize, usize> = HashMap::new();
+        for &key in &row_keys {
+            let root = sets.find(key);
+            let next_id = cluster_ids.len();
+            let id = *cluster_ids.entry(root).or_insert(next_id);
+            if id == clustering.clusters.len() {
+                clustering.clusters.push(Cluster::default());
+            }
+            clustering.clusters[id].size += 1;
+            clustering.cluster_ids.push(id);
+        }
+
+        let mut best: HashMap<usize, (&(String, String), &Spelling)> = HashMap::new();
+        for (names, spelling) in &spellings {
+            let id = cluster_ids[&sets.find(spelling.key)];
+            let rank = |(names, spelling): (&(String, String), &Spelling)| {
+                (
+                    spelling.count,
+                    is_mixed_case(&names.0, &names.1),
+                    std::cmp::Reverse(spelling.first_row),
+                )
+            };
+            let current = best.entry(id).or_insert((names, spelling));
+            if rank((names, spelling)) > rank(*current) {
+                *current = (names, spelling);
+            }
+        }
+        for (id, ((artist, title), spelling)) in best {
+            let cluster = &mut clustering.clusters[id];
+            cluster.key = keys[spelling.key].clone();
+            cluster.artist = artist.clone();
+            cluster.title = title.clone();
+        }
+        clustering
+    }
+
+    /// Split the distinct keys into blocks that share their artists, and
+    /// then the prefixes of their titles if there are too many titles.
+    fn blocks<'k>(&self, keys: &'k [String]) -> Vec<Vec<Entry<'k>>> {
+        let mut by_artists: HashMap<&str, Vec<Entry>> = HashMap::new();
+        for (id, key) in keys.iter().enumerate() {
+            let (artists, title) = key.split_once(" - ").unwrap_or(("", key));
+            by_artists
+                .entry(artists)
+                .or_default()
+                .push((id, title, title.chars().count()));
+        }
+        let mut blocks = Vec::new();
+        for block in by_artists.into_values() {
+            split_block(block, 0, &mut blocks);
+        }
+        blocks
+    }
+
+    /// Merge the similar titles of a block.
+    fn merge_block(&self, block: &mut [Entry], sets: &mut UnionFind) {
+        if self.threshold >= 1.0 {
+            return;
+        }
+        block.sort_by_key(|&(_, _, len)| len);
+        for (i, &(id, title, len)) in block.iter().enumerate() {
+            for &(other_id, other_title, other_len) in &block[i + 1..] {
+                // Titles sorted by length only get further apart, and a
+                // longer title needs more edits than its extra length.
+                if (len as f64) < self.threshold * other_len as f64 {
+                    break;
+                }
+                if edit_similarity(title, other_title) >= self.threshold {
+                    sets.union(id, other_id);
+                }
+            }
+        }
+    }
+}
+
+/// Adds `block` to `blocks`, split by the character at `depth` in its
+/// titles, and then the next ones, while it has more than [`MAX_BLOCK_LEN`]
+/// titles.
+fn split_block<'k>(block: Vec<Entry<'k>>, depth: usize, blocks: &mut Vec<Vec<Entry<'k>>>) {
+    if block.len() <= MAX_BLOCK_LEN {
+        blocks.push(block);
+        return;
+    }
+    let mut by_prefix: HashMap<Option<char>, Vec<Entry>> = HashMap::new();
+    for entry in block {
+        by_prefix
+            .entry(entry.1.chars().nth(depth))
+            .or_default()
+            .push(entry);
+    }
+    for block in by_prefix.into_values() {
+        // Titles are distinct, so at most one of them ends at `depth`.
+        split_block(block, depth + 1, blocks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dedup::*;
+
+    #[test]
+    fn deduplicator_1() {
+        let rows = [
+            ("Beyoncé", "Crazy In Love (feat. JAY-Z)"),
+            ("Beyonce & Jay-Z", "crazy in love"),
+            ("Beyoncé", "Halo"),
+            ("BEYONCE", "CRAZY IN LOVE (FEAT. JAY-Z) [Official Video]"),
+            ("Beyoncé", "Crazy in Lov (feat. Jay-Z)"),
+            ("Beyoncé", "Crazy In Love (feat. JAY-Z)"),
+        ];
+        let actual = Deduplicator::default().cluster(rows.iter().copied());
+        assert_eq!(actual.cluster_ids, vec![0, 0, 1, 0, 0, 0]);
+        assert_eq!(
+            actual.clusters[0],
+            Cluster {
+                key: "beyonce, jay z - crazy in love".to_string(),
+                artist: "Beyoncé".to_string(),
+                title: "Crazy In Love (feat. JAY-Z)".to_string(),
+                size: 5,
+            }
+        );
+        assert_eq!(actual.clusters[1].title, "Halo");
+    }
+    #[test]
+    fn deduplicator_2() {
+        let rows = vec![
+            ("Artist".to_string(), "Song".to_string()),
+            ("Artist".to_string(), "Song (Remix)".to_string()),
+            ("Other Artist".to_string(), "Song".to_string()),
+            ("Artist".to_string(), "Sogn".to_string()),
+        ];
+        let actual = Deduplicator::default().cluster(rows.clone());
+        assert_eq!(actual.cluster_ids, vec![0, 1, 2, 3]);
+        let actual = Deduplicator::default().threshold(0.5).cluster(rows);
+        assert_eq!(actual.cluster_ids, vec![0, 1, 2, 0]);
+        assert_eq!(actual.clusters[0].size, 2);
+        assert_eq!(
+            Deduplicator::default().cluster(Vec::<(&str, &str)>::new()),
+            Clustering::default()
+        );
+    }
+    #[test]
+    fn deduplicator_3() {
+        let mut rows: Vec<(String, String)> = (0..1500)
+            .map(|n| ("Artist".to_string(), format!("Song {}", n)))
+            .collect();
+        rows.push(("Artist".to_string(), "Song 1234x".to_string()));
+        let actual = Deduplicator::default().cluster(rows);
+        assert_eq!(actual.clusters.len(), 1500);
+        assert_eq!(actual.cluster_ids[1234], actual.cluster_ids[1500]);
+    }
+    #[test]
+    fn deduplicator_4() {
+        let rows = [
+            ("Earth, Wind & Fire", "September"),
+            ("Earth, Wind and Fire", "September"),
+        ];
+        let actual = Deduplicator::default().cluster(rows.iter().copied());
+        assert_eq!(actual.cluster_ids, vec![0, 0]);
+    }
+}
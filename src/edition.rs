@@ -0,0 +1,160 @@
+//! Functions to parse edition, disc and explicitness annotations out of album titles.
+//!
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::{fix_common, normalize_unicode};
+
+lazy_static! {
+    static ref EXPLICIT_REGEX: Regex = Regex::new(
+        r"(?i)[\(\[][[:space:]]*(explicit|clean)(?:[[:space:]]+(?:version|content|lyrics))?[[:space:]]*[\)\]]"
+    ).unwrap();
+    static ref EDITION_REGEX: Regex = Regex::new(
+        r"(?i)[\(\[]([^\(\)\[\]]*\b(?:edition|expanded|deluxe|anniversary|bonus[[:space:]]+tracks?|remaster(?:ed)?|reissue)\b[^\(\)\[\]]*)[\)\]]"
+    ).unwrap();
+    // A disc annotation is either a whole bracket group, as in `[CD2]`, or
+    // trails the title, as in `Album - Disc 1 of 2`, so that titles such as
+    // `CD 2 Go` and groups such as `(Live, Disc 2)` are left as they are.
+    static ref DISC_REGEX: Regex = {
+        let disc = r"(?:disc|disk|cd)[[:space:]]*([0-9]{1,2})(?:[[:space:]]*(?:of|/)[[:space:]]*[0-9]{1,2})?";
+        Regex::new(&format!(
+            r"(?i)\([[:space:]]*{disc}[[:space:]]*\)|\[[[:space:]]*{disc}[[:space:]]*\]|(?:^|[[:space:]]*[-–:,]|[[:space:]])[[:space:]]*{disc}[[:space:]]*$",
+            disc = disc
+        ))
+        .unwrap()
+    };
+}
+
+/// Whether an album is labeled as the explicit or the clean version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Explicitness {
+    /// `(Explicit)` or `[Explicit Version]`.
+    Explicit,
+    /// `[Clean]` or `(Clean Version)`.
+    Clean,
+}
+
+/// An album title with its edition, disc and explicitness annotations split out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlbumEdition {
+    /// The cleaned album title, without any of these annotations.
+    pub title: String,
+    /// The edition, as written, such as `Deluxe Edition` or `Bonus Track Version`.
+    pub edition: Option<String>,
+    /// The disc number, from annotations such as `Disc 1` or `CD2`.
+    pub disc: Option<u32>,
+    /// Whether the album is labeled as explicit or clean.
+    pub explicitness: Option<Explicitness>,
+}
+
+/// Removes edition, disc and explicitness annotations from `dirty`,
+/// storing them in `edition`. The returned string is normalized with
+/// [`normalize_unicode`], but not cleaned.
+pub(crate) fn remove_album_annotations(dirty: &str, edition: &mut AlbumEdition) -> String {
+    let dirty = normalize_unicode(dirty);
+    let dirty_1 = EXPLICIT_REGEX.replace_all(&dirty, |caps: &Captures| {
+        if edition.explicitness.is_none() {
+            edition.explicitness = if caps[1].eq_ignore_ascii_case("clean") {
+                Some(Explicitness::Clean)
+            } else {
+                Some(Explicitness::Explicit)
+            };
+        }
+        " "
+    });
+    let dirty_2 = EDITION_REGEX.replace_all(&dirty_1, |caps: &Captures| {
+        if edition.edition.is_none() {
+            edition.edition = Some(caps[1].trim().to_string());
+        }
+        " "
+    });
+    let dirty_3 = DISC_REGEX.replace_all(&dirty_2, |caps: &Captures| {
+        if edition.disc.is_none() {
+            edition.disc = caps
+                .iter()
+                .skip(1)
+                .flatten()
+                .next()
+                .and_then(|number| number.as_str().parse().ok());
+        }
+        " "
+    });
+    dirty_3.into_owned()
+}
+
+/// Split a raw album title into the title and its edition annotations.
+///
+/// These annotations are recognized:
+/// - editions: `(Deluxe Edition)`, `[Expanded]`, `(Anniversary Edition)`,
+///   `(Bonus Track Version)`, `(Remastered)`
+/// - discs: `[Disc 2 of 3]`, `(CD2)`, or `Disc 1` and `CD2` at the end of the title
+/// - explicitness: `(Explicit)`, `[Clean]`
+///
+/// The remaining title is cleaned with [`fix_common`].
+///
+pub fn parse_album_edition(dirty: &str) -> AlbumEdition {
+    let mut edition = AlbumEdition::default();
+    let title = remove_album_annotations(dirty, &mut edition);
+    edition.title = fix_common(&title);
+    edition
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::edition::*;
+
+    #[test]
+    fn parse_album_edition_1() {
+        let actual = parse_album_edition("Views (Deluxe Edition) [Explicit]");
+        assert_eq!(actual.title, "Views");
+        assert_eq!(actual.edition.as_deref(), Some("Deluxe Edition"));
+        assert_eq!(actual.disc, None);
+        assert_eq!(actual.explicitness, Some(Explicitness::Explicit));
+    }
+    #[test]
+    fn parse_album_edition_2() {
+        let actual = parse_album_edition("The Wall - Disc 2 of 2 [Clean]");
+        assert_eq!(actual.title, "The Wall");
+        assert_eq!(actual.disc, Some(2));
+        assert_eq!(actual.explicitness, Some(Explicitness::Clean));
+    }
+    #[test]
+    fn parse_album_edition_3() {
+        let actual = parse_album_edition("Abbey Road [50th Anniversary Edition] CD1");
+        assert_eq!(actual.title, "Abbey Road");
+        assert_eq!(actual.edition.as_deref(), Some("50th Anniversary Edition"));
+        assert_eq!(actual.disc, Some(1));
+    }
+    #[test]
+    fn parse_album_edition_4() {
+        let actual = parse_album_edition("Discovery (Bonus Track Version)");
+        assert_eq!(actual.title, "Discovery");
+        assert_eq!(actual.edition.as_deref(), Some("Bonus Track Version"));
+        assert_eq!(actual.disc, None);
+    }
+    #[test]
+    fn parse_album_edition_5() {
+        let actual = parse_album_edition("Views (Live, Disc 2)");
+        assert_eq!(actual.title, "Views (Live, Disc 2)");
+        assert_eq!(actual.disc, None);
+        let actual = parse_album_edition("CD 2 Go");
+        assert_eq!(actual.title, "CD 2 Go");
+        assert_eq!(actual.disc, None);
+        let actual = parse_album_edition("Views (Disc 2) [Explicit]");
+        assert_eq!(actual.title, "Views");
+        assert_eq!(actual.disc, Some(2));
+        let actual = parse_album_edition("The Wall, CD 1/2");
+        assert_eq!(actual.title, "The Wall");
+        assert_eq!(actual.disc, Some(1));
+    }
+    #[test]
+    fn parse_album_edition_6() {
+        let actual = parse_album_edition("Album （Deluxe Edition）【Explicit】");
+        assert_eq!(actual.title, "Album");
+        assert_eq!(actual.edition.as_deref(), Some("Deluxe Edition"));
+        assert_eq!(actual.explicitness, Some(Explicitness::Explicit));
+    }
+}
@@ -0,0 +1,226 @@
+//! Explanations of which cleaning rule changed which part of a string.
+//!
+use std::ops::Range;
+
+use lazy_static::lazy_static;
+
+use crate::{Cleaner, Pipeline};
+
+lazy_static! {
+    static ref DEFAULT_PIPELINE: Pipeline = Pipeline::default();
+}
+
+/// A single replacement made by a cleaning step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edit {
+    /// The byte range of the step's input that was replaced.
+    pub span: Range<usize>,
+    /// The text that replaced it.
+    pub replacement: String,
+}
+
+/// A replacement made by a rule, located in the original input string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleApplication {
+    /// The name of the rule, as returned by [`Cleaner::name`].
+    pub rule: String,
+    /// The byte range of the original input that the rule replaced.
+    ///
+    /// If an earlier rule already changed this part of the string, the span
+    /// covers the original text that those changes came from.
+    pub span: Range<usize>,
+    /// The original input text in `span`.
+    pub matched: String,
+    /// The text that the rule put in its place.
+    pub replacement: String,
+}
+
+/// The result of cleaning a string, with every rule application that led to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Explanation {
+    /// The cleaned string.
+    pub cleaned: String,
+    /// The rule applications, in the order they were made.
+    pub applications: Vec<RuleApplication>,
+}
+
+/// Returns `dirty` with `edits` applied. The edits must be sorted and must not overlap.
+pub(crate) fn apply_edits(dirty: &str, edits: &[Edit]) -> String {
+    let mut applied = String::with_capacity(dirty.len());
+    let mut last = 0;
+    for edit in edits {
+        applied.push_str(&dirty[last..edit.span.start]);
+        applied.push_str(&edit.replacement);
+        last = edit.span.end;
+    }
+    applied.push_str(&dirty[last..]);
+    applied
+}
+
+/// Returns a single edit that turns `before` into `after`, covering
+/// everything between their common prefix and common suffix.
+pub(crate) fn diff_edits(before: &str, after: &str) -> Vec<Edit> {
+    if before == after {
+        return Vec::new();
+    }
+    let prefix = before
+        .char_indices()
+        .zip(after.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(before.len().min(after.len()), |((index, _), _)| index);
+    let suffix = before[prefix..]
+        .chars()
+        .rev()
+        .zip(after[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+    vec![Edit {
+        span: prefix..before.len() - suffix,
+        replacement: after[prefix..after.len() - suffix].to_string(),
+    }]
+}
+
+/// For every byte position of a string, the original byte positions that it
+/// maps to when it starts a span and when it ends a span.
+type PositionMap = Vec<(usize, usize)>;
+
+/// Returns the position map of the string that results from applying `edits`.
+fn remap(map: &[(usize, usize)], edits: &[Edit]) -> PositionMap {
+    let mut remapped = Vec::with_capacity(map.len());
+    let mut last = 0;
+    for edit in edits {
+        let (start, end) = (edit.span.start, edit.span.end);
+        remapped.extend_from_slice(&map[last..start]);
+        for index in 0..edit.replacement.len() {
+            if index == 0 {
+                remapped.push(map[start]);
+            } else {
+                remapped.push((map[start].0, map[end].1));
+            }
+        }
+        last = end;
+    }
+    remapped.extend_from_slice(&map[last..]);
+    remapped
+}
+
+/// Apply `steps` to `dirty`, in order, recording every replacement that each step makes.
+pub(crate) fn explain_steps(steps: &[Box<dyn Cleaner>], dirty: &str) -> Explanation {
+    let mut current = dirty.to_string();
+    let mut map: PositionMap = (0..=dirty.len()).map(|index| (index, index)).collect();
+    let mut applications = Vec::new();
+    for step in steps {
+        let cleaned = step.clean(&current).into_owned();
+        if cleaned == current {
+            continue;
+        }
+        let mut edits = step.edits(&current);
+        if apply_edits(&current, &edits) != cleaned {
+            edits = diff_edits(&current, &cleaned);
+        }
+        for edit in &edits {
+            let start = map[edit.span.start].0;
+            let end = map[edit.span.end].1.max(start);
+            applications.push(RuleApplication {
+                rule: step.name().to_string(),
+                span: start..end,
+                matched: dirty[start..end].to_string(),
+                replacement: edit.replacement.clone(),
+            });
+        }
+        map = remap(&map, &edits);
+        current = cleaned;
+    }
+    Explanation {
+        cleaned: current,
+        applications,
+    }
+}
+
+/// Clean a string like [`crate::fix_common`], and explain which rule made each change.
+///
+/// Use [`Pipeline::explain`] to explain a custom pipeline.
+///
+pub fn explain(dirty: &str) -> Explanation {
+    DEFAULT_PIPELINE.explain(dirty)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::explain::*;
+    use crate::*;
+
+    fn summary(explanation: &Explanation) -> Vec<(&str, Range<usize>, &str, &str)> {
+        explanation
+            .applications
+            .iter()
+            .map(|a| {
+                (
+                    a.rule.as_str(),
+                    a.span.clone(),
+                    a.matched.as_str(),
+                    a.replacement.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn explain_1() {
+        let dirty = "IGOR (2019) Mp3 (320 kbps)";
+        let actual = explain(dirty);
+        assert_eq!(actual.cleaned, fix_common(dirty));
+        assert_eq!(
+            summary(&actual),
+            vec![
+                ("year_annotation", 5..11, "(2019)", " "),
+                ("format_annotation", 12..15, "Mp3", " "),
+                ("bitrate_annotation", 16..26, "(320 kbps)", " "),
+                ("redundant_whitespace", 4..26, " (2019) Mp3 (320 kbps)", ""),
+            ]
+        );
+    }
+    #[test]
+    fn explain_2() {
+        let dirty = "IGOR\u{00A0}[WEB] [FLAC]";
+        let actual = explain(dirty);
+        assert_eq!(actual.cleaned, "IGOR");
+        assert_eq!(
+            summary(&actual)[..3],
+            [
+                ("unicode_normalization", 4..6, "\u{00A0}", " "),
+                ("format_annotation", 6..11, "[WEB]", " "),
+                ("format_annotation", 12..18, "[FLAC]", " "),
+            ]
+        );
+    }
+    #[test]
+    fn explain_3() {
+        let actual = explain("IGOR");
+        assert_eq!(actual.cleaned, "IGOR");
+        assert!(actual.applications.is_empty());
+    }
+    #[test]
+    fn diff_edits_1() {
+        let edits = diff_edits("Don’t Stop", "Don't Stop");
+        assert_eq!(apply_edits("Don’t Stop", &edits), "Don't Stop");
+        assert_eq!(edits[0].span, 3..6);
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn explain_serde_1() {
+        let actual = explain("IGOR [Mp3]");
+        let json = serde_json::to_value(&actual).unwrap();
+        assert_eq!(json["cleaned"], "IGOR");
+        assert_eq!(json["applications"][0]["rule"], "format_annotation");
+        assert_eq!(
+            json["applications"][0]["span"],
+            serde_json::json!({"start": 5, "end": 10})
+        );
+        assert_eq!(serde_json::from_value::<Explanation>(json).unwrap(), actual);
+    }
+}
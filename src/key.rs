@@ -0,0 +1,91 @@
+//! Canonical keys that two spellings of the same track share.
+//!
+use crate::similarity::normalized_words;
+use crate::{fix_artists_string, parse_track_credits};
+
+/// Reduce a cleaned name to lowercase words without diacritics,
+/// punctuation or a leading `the`.
+fn canonical_name(cleaned: &str) -> String {
+    let mut words = normalized_words(cleaned);
+    if words.len() > 1 && words[0] == "the" {
+        words.remove(0);
+    }
+    words.join(" ")
+}
+
+/// Build a canonical key for a raw track title and a raw artists string,
+/// for grouping the spellings of the same track.
+///
+/// The title is cleaned with [`crate::parse_track_credits`], which runs
+/// [`crate::fix_track_title`], and the artists with
+/// [`crate::fix_artists_string`]. Featured artists, whether credited in
+/// the title or in the artists string, join the artists. Then every name is
+/// reduced to an aggressive canonical form:
+/// - lowercase, without diacritics, so `Beyoncé` becomes `beyonce`
+/// - without punctuation, so `JAY-Z` becomes `jay z` and `Don't` becomes `dont`
+/// - with `&` written as `and`
+/// - without a leading `The`, unless it is the whole name
+///
+/// The key is the sorted, deduplicated artists joined with `, `, then
+/// ` - ` and the title, as in `beyonce, jay z - crazy in love`. Version tags
+/// such as `(Remix)` are kept, since a remix is a different recording.
+///
+pub fn match_key(title: &str, artists: &str) -> String {
+    let credits = parse_track_credits(title);
+    let mut names: Vec<String> = credits.featured_artists;
+    for artist in fix_artists_string(artists) {
+        let artist_credits = parse_track_credits(&artist);
+        names.push(artist_credits.title);
+        names.extend(artist_credits.featured_artists);
+    }
+    let mut names: Vec<String> = names
+        .iter()
+        .map(|name| canonical_name(name))
+        .filter(|name| !name.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+    format!("{} - {}", names.join(", "), canonical_name(&credits.title))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::key::*;
+
+    #[test]
+    fn match_key_1() {
+        let expected = "beyonce, jay z - crazy in love";
+        assert_eq!(
+            match_key("Crazy In Love (feat. JAY-Z) [Official Video]", "Beyoncé"),
+            expected
+        );
+        assert_eq!(match_key("crazy in love", "Jay-Z & Beyonce"), expected);
+        assert_eq!(match_key("Crazy in Love", "Beyoncé feat. Jay Z"), expected);
+    }
+    #[test]
+    fn match_key_2() {
+        assert_eq!(
+            match_key("The Less I Know The Better", "Tame Impala"),
+            "tame impala - less i know the better"
+        );
+        assert_eq!(
+            match_key("03. Rock & Roll (2011)", "The Beatles"),
+            match_key("Rock and Roll", "Beatles")
+        );
+        assert_eq!(match_key("The", "The The"), "the - the");
+        assert_ne!(
+            match_key("Song (Remix)", "Artist"),
+            match_key("Song", "Artist")
+        );
+    }
+    #[test]
+    fn match_key_3() {
+        let expected = "earth wind and fire - september";
+        assert_eq!(match_key("September", "Earth, Wind & Fire"), expected);
+        assert_eq!(match_key("September", "Earth, Wind and Fire"), expected);
+        assert_eq!(
+            match_key("Mrs. Robinson", "Simon and Garfunkel"),
+            match_key("Mrs. Robinson", "Simon & Garfunkel")
+        );
+    }
+}
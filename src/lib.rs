@@ -11,13 +11,75 @@ use std::ops::Deref;
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use crate::brackets::{annotation_edits, bracket_debris_edits, remove_annotations};
+use crate::explain::{apply_edits, diff_edits};
+use crate::year::DEFAULT_YEAR_REMOVER;
+
+mod artist_title;
+mod artists;
+mod batch;
+mod brackets;
+mod casing;
+mod credits;
+mod dedup;
+mod edition;
+mod engine;
+mod explain;
+mod key;
+mod metadata;
+mod path;
+mod pipeline;
+mod release;
+mod rules;
+mod similarity;
+mod track_number;
+mod unicode;
+mod upload;
+mod version;
+mod year;
+
+pub use artist_title::{split_artist_and_title, ArtistTitle};
+pub use artists::{split_artists, ArtistSplitter};
+pub use batch::{clean_batch, clean_batch_iter};
+pub use casing::title_case;
+pub use credits::{parse_track_credits, TrackCredits};
+pub use dedup::{Cluster, Clustering, Deduplicator};
+pub use edition::{parse_album_edition, AlbumEdition, Explicitness};
+pub use engine::fix_common_into;
+pub use explain::{explain, Edit, Explanation, RuleApplication};
+pub use key::match_key;
+pub use metadata::{
+    parse_album_title, parse_artists_string, parse_common, parse_track_title, AudioFormat,
+    AudioSource, CleanedMetadata,
+};
+pub use path::{parse_path, PathMetadata};
+pub use pipeline::{Cleaner, Pipeline, PipelineBuilder, Step};
+pub use release::{parse_release_name, ReleaseInfo};
+pub use rules::{Field, Rule, RuleError, RuleSet};
+pub use similarity::{
+    artists_similarity, edit_similarity, levenshtein, title_similarity, token_similarity,
+};
+pub use track_number::{parse_track_number, parse_track_number_with_bare_sides, NumberedTitle};
+pub use unicode::normalize_unicode;
+pub use upload::remove_upload_noise;
+pub use version::{parse_track_version, TrackVersion, VersionType, VersionedTitle};
+pub use year::YearRemover;
+
 lazy_static! {
-    static ref BITRATE_REGEX: Regex = Regex::new(r"[\(|[[:punct:]]|[[:space:]]]?(?i)\d+[[:space:]]]*kbps[\)|[[:punct:]]|[[:space:]]]?").unwrap();
-    static ref MP3_REGEX: Regex = Regex::new(r"[\(|[[:punct:]]|[[:space:]]]?(?i)mp3[\)|[[:punct:]]|[[:space:]]]?").unwrap();
-    // Require that year annotations are encased in punctuation
-    // to avoid mangling artist names and album titles that happen to have
-    // numbers in them.
-    static ref YEAR_REGEX: Regex = Regex::new(r"[\(|[[:punct:]]]?(?:19|20)[0-9]{2}[\)|[[:punct:]]]").unwrap();
+    static ref BITRATE_REGEX: Regex = Regex::new(r"(?i)\b(?:[0-9]+[[:space:]]*(?:kbps|cbr|vbr)|[cv]br)\b").unwrap();
+    // Common bitrates, which are only removed when alone inside brackets,
+    // such as `[320]`.
+    static ref BRACKETED_BITRATE_REGEX: Regex = Regex::new(r"\b(?:96|128|160|192|224|256|320)\b").unwrap();
+    // Format and quality labels that are never part of a real title.
+    static ref FORMAT_REGEX: Regex = Regex::new(r"(?i)\b(?:mp3|flac|alac|m4a|(?:16|24)[-/](?:44\.1|48|88\.2|96|176\.4|192)(?:[[:space:]]*khz)?|hi-?res|vinyl[- ]?rip)\b").unwrap();
+    // Format and source labels that are also ordinary words, such as
+    // "Charlotte's Web", "Opus 27", "The Wav" or "The 24-Bit Song", so we
+    // only remove them when they are alone inside brackets.
+    static ref BRACKETED_FORMAT_REGEX: Regex = Regex::new(r"(?i)\b(?:opus|web|cd|vinyl|aac|ogg|wav|lossless|(?:16|24|32)[- ]?bits?)\b").unwrap();
+    // MP3 encoder presets. Uploads also use them for "version 2", as in
+    // `Song (V2)`, so we only remove them in brackets with another format
+    // label, as in `[MP3 V0]`.
+    static ref MP3_PRESET_REGEX: Regex = Regex::new(r"(?i)\bv[02]\b").unwrap();
     static ref REDUNDANT_WHITESPACE_REGEX: Regex = Regex::new(r"[[:space:]]+").unwrap();
     static ref BEGINNING_WHITESPACE_REGEX: Regex =  Regex::new(r"^[[:space:]]+").unwrap();
     static ref ENDING_WHITESPACE_REGEX: Regex =  Regex::new(r"[[:space:]]+$").unwrap();
@@ -25,26 +87,109 @@ lazy_static! {
 
 /// Remove "year annotations" from strings.
 ///
-/// A year annotation is a year encased in punctuation, such as:
+/// A year annotation is a year alone in brackets, or a year trailing the
+/// string after a separator, such as:
 /// - `[2019]`
 /// - `(1997)`
+/// - `Album - 2019`
+///
+/// See [`YearRemover`] for the years that are kept.
 ///
 fn remove_year_annotation(dirty: &str) -> Cow<'_, str> {
-    YEAR_REGEX.replace_all(dirty, " ")
+    DEFAULT_YEAR_REMOVER.clean(dirty)
 }
 
 /// Remove music bitrate annotations from strings.
 ///
 /// A bitrate annotation looks like:
 /// - `(128kbps)`
+/// - `320 CBR`
+/// - `[VBR]`
+/// - `[320]`, for the common bitrates alone in brackets
+///
+/// Brackets are removed along with the annotations they enclose.
 ///
 fn remove_bitrate_annotation(dirty: &str) -> Cow<'_, str> {
-    BITRATE_REGEX.replace_all(dirty, " ")
+    remove_annotations(dirty, &[&BITRATE_REGEX], &[&BRACKETED_BITRATE_REGEX])
 }
 
-/// Removes the case-insensitive string `mp3` from the input string.
-fn remove_mp3_format_label(dirty: &str) -> Cow<'_, str> {
-    MP3_REGEX.replace_all(dirty, " ")
+/// The edits made by [`remove_bitrate_annotation`].
+fn bitrate_annotation_edits(dirty: &str) -> Vec<Edit> {
+    annotation_edits(dirty, &[&BITRATE_REGEX], &[&BRACKETED_BITRATE_REGEX])
+}
+
+/// Remove audio format and quality annotations from strings.
+///
+/// These labels are removed wherever they appear, case-insensitively:
+/// - codecs: `mp3`, `FLAC`, `ALAC`, `M4A`
+/// - sample rate: `16-44.1`, `24/96`
+/// - quality: `Hi-Res`, `Vinyl rip`
+///
+/// The labels `Opus`, `WEB`, `CD`, `Vinyl`, `AAC`, `OGG`, `WAV`,
+/// `Lossless` and bit depths such as `24bit` or `16-bit` are only removed
+/// when a bracket group holds nothing but format labels, such as `[WEB]`,
+/// `[WEB FLAC]` or `(24-bit)`. The MP3 presets `V0`
+/// and `V2` are only removed along with another format label, as in
+/// `[MP3 V0]`. Brackets are removed along with the labels they enclose.
+///
+fn remove_format_annotation(dirty: &str) -> Cow<'_, str> {
+    let edits = format_annotation_edits(dirty);
+    if edits.is_empty() {
+        Cow::Borrowed(dirty)
+    } else {
+        Cow::Owned(apply_edits(dirty, &edits))
+    }
+}
+
+/// The edits made by [`remove_format_annotation`].
+fn format_annotation_edits(dirty: &str) -> Vec<Edit> {
+    let format_labels = [&*FORMAT_REGEX, &*BRACKETED_FORMAT_REGEX];
+    annotation_edits(
+        dirty,
+        &[&FORMAT_REGEX],
+        &[&BRACKETED_FORMAT_REGEX, &MP3_PRESET_REGEX],
+    )
+    .into_iter()
+    .filter(|edit| {
+        let removed = &dirty[edit.span.clone()];
+        !MP3_PRESET_REGEX.is_match(removed)
+            || format_labels.iter().any(|regex| regex.is_match(removed))
+    })
+    .collect()
+}
+
+/// Remove brackets that are empty, or that hold only separators such as
+/// `( - )`, which are often left behind by the other cleaning steps.
+///
+/// The other bracket debris of those steps is removed as well: brackets
+/// whose partner was removed, as in `IGOR (`, and whitespace just inside
+/// brackets, as in `Title ( Remix)`.
+///
+fn remove_empty_brackets(dirty: &str) -> Cow<'_, str> {
+    // Removing debris can empty a group, and removing a group can leave
+    // whitespace inside the group around it.
+    let mut text = Cow::Borrowed(dirty);
+    loop {
+        let mut edits = annotation_edits(&text, &[], &[]);
+        if edits.is_empty() {
+            edits = bracket_debris_edits(&text);
+        }
+        if edits.is_empty() {
+            return text;
+        }
+        text = Cow::Owned(apply_edits(&text, &edits));
+    }
+}
+
+/// The edits made by [`remove_empty_brackets`].
+fn empty_brackets_edits(dirty: &str) -> Vec<Edit> {
+    let edits = annotation_edits(dirty, &[], &[]);
+    let clean = remove_empty_brackets(dirty);
+    if apply_edits(dirty, &edits) == clean {
+        edits
+    } else {
+        diff_edits(dirty, &clean)
+    }
 }
 
 /// Removes "unnecessary" whitespace from a string.
@@ -61,28 +206,108 @@ fn remove_redundant_whitespace(dirty: &str) -> String {
     dirty_3.deref().to_string()
 }
 
+/// The edits made by [`remove_redundant_whitespace`].
+fn redundant_whitespace_edits(dirty: &str) -> Vec<Edit> {
+    REDUNDANT_WHITESPACE_REGEX
+        .find_iter(dirty)
+        .filter_map(|m| {
+            let replacement = if m.start() == 0 || m.end() == dirty.len() {
+                ""
+            } else if m.as_str() == " " {
+                return None;
+            } else {
+                " "
+            };
+            Some(Edit {
+                span: m.range(),
+                replacement: replacement.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Builds a case-insensitive regex that matches any of `names` as whole
+/// words, preferring the longest name, so that `Crosby, Stills, Nash &
+/// Young` wins over `Crosby, Stills & Nash`.
+fn names_regex<S: AsRef<str>>(names: &[S]) -> Regex {
+    names_regex_with(names, regex::escape)
+}
+
+/// Like [`names_regex`], but with `pattern` turning each name into the
+/// regex that matches it.
+fn names_regex_with<S: AsRef<str>>(names: &[S], pattern: fn(&str) -> String) -> Regex {
+    let mut names: Vec<&str> = names.iter().map(AsRef::as_ref).collect();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+    let alternation = names
+        .iter()
+        .map(|name| pattern(name))
+        .collect::<Vec<_>>()
+        .join("|");
+    Regex::new(&format!(r"(?i)(?:^|\b)(?:{})(?:\b|$)", alternation)).unwrap()
+}
+
+/// Returns `true` if byte offset `index` of `dirty` is inside parentheses or brackets.
+fn is_inside_brackets(dirty: &str, index: usize) -> bool {
+    let mut depth = 0usize;
+    for c in dirty[..index].chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
 /// Applies a common set of input transformations to every string.
+///
+/// Use [`parse_common`] to also get the annotations that were removed, or
+/// [`fix_common_into`] to write into a reusable buffer.
+///
+/// Year annotations are always removed with the default [`YearRemover`].
+/// To protect more titles, replace the `year_annotation` step of a
+/// [`Pipeline`] with a customized [`YearRemover`]:
+///
+/// ```
+/// use music_metadata_cleaner::{Pipeline, YearRemover};
+///
+/// let pipeline = Pipeline::builder()
+///     .replace("year_annotation", YearRemover::default().protect("2046"))
+///     .build();
+/// assert_eq!(pipeline.clean("Wong Kar-wai - 2046"), "Wong Kar-wai - 2046");
+/// ```
 pub fn fix_common(dirty: &str) -> String {
-    let dirty_1 = remove_year_annotation(dirty);
-    let dirty_2 = remove_mp3_format_label(&dirty_1);
-    let dirty_3 = remove_bitrate_annotation(&dirty_2);
-    let dirty_4 = remove_redundant_whitespace(&dirty_3);
-    dirty_4.deref().to_string()
+    let mut cleaned = String::with_capacity(dirty.len());
+    fix_common_into(dirty, &mut cleaned);
+    cleaned
 }
 
 /// Clean a raw string that represents a music album title.
+///
+/// In addition to [`fix_common`], this removes edition, disc and
+/// explicitness annotations. Use [`parse_album_edition`] to keep them.
+///
+/// Like [`fix_common`], this only protects the built-in year titles, so
+/// an album titled after another year loses it when it trails the string.
 pub fn fix_album_title(dirty: &str) -> String {
-    fix_common(dirty).to_string()
+    parse_album_edition(dirty).title
 }
 
 /// Clean a raw string that represents the title of a single music song or track.
+///
+/// In addition to [`fix_common`], this removes track number prefixes such
+/// as `03. ` or `A1 `, and upload noise such as `(Official Music Video)`;
+/// see [`remove_upload_noise`]. Use [`parse_track_number`] to keep the
+/// track number. Years are removed as in [`fix_common`].
 pub fn fix_track_title(dirty: &str) -> String {
-    fix_common(dirty).to_string()
+    parse_track_number(dirty).title
 }
 
 /// Clean a raw string that represents one or more artists. Returns a vector of artist names.
+///
+/// See [`split_artists`] for the separators that are recognized.
 pub fn fix_artists_string(dirty: &str) -> Vec<String> {
-    vec![fix_common(dirty).to_string()]
+    split_artists(&fix_common(dirty))
 }
 
 #[cfg(test)]
@@ -99,4 +324,194 @@ mod tests {
         let actual = fix_album_title("Tyler, The Creator - IGOR (2019) [Mp3] (320 kbps)");
         assert_eq!(actual, "Tyler, The Creator - IGOR");
     }
+    #[test]
+    fn fix_album_title_3() {
+        let actual = fix_album_title("Snoop Dogg - Doggystyle (1993) [FLAC 24bit] [WEB]");
+        assert_eq!(actual, "Snoop Dogg - Doggystyle");
+    }
+    #[test]
+    fn fix_album_title_4() {
+        let actual = fix_album_title("Tyler, The Creator - IGOR (2019) [Explicit] [Mp3] (320 kbps)");
+        assert_eq!(actual, "Tyler, The Creator - IGOR");
+    }
+    #[test]
+    fn fix_track_title_2() {
+        assert_eq!(
+            fix_track_title("03. Song (Official Music Video) [HD] (320 kbps) | Lyrics 🔥"),
+            "Song"
+        );
+        assert_eq!(fix_track_title("I ❤ NY 🔥🔥"), "I ❤ NY");
+        assert_eq!(fix_track_title("Gold ☆ Star"), "Gold ☆ Star");
+    }
+    #[test]
+    fn fix_track_title_1() {
+        assert_eq!(fix_track_title("03. 7 Rings (2019) [Mp3]"), "7 Rings");
+        assert_eq!(fix_track_title("99 Problems"), "99 Problems");
+    }
+    #[test]
+    fn fix_track_title_3() {
+        for title in &[
+            "Hold On V2",
+            "Song (V2)",
+            "The Wav",
+            "Lossless Love",
+            "Ogg Vorbis Blues",
+        ] {
+            assert_eq!(fix_track_title(title), *title);
+        }
+        assert_eq!(fix_track_title("Song [MP3 V0]"), "Song");
+        assert_eq!(fix_track_title("Song (WAV) [Lossless, V2]"), "Song");
+    }
+    #[test]
+    fn fix_album_title_5() {
+        let actual = fix_album_title("Tyler,\u{00A0}The Creator – IGOR （２０１９） 【Mp3】 (320 kbps)");
+        assert_eq!(actual, "Tyler, The Creator – IGOR");
+    }
+    #[test]
+    fn fix_common_1() {
+        assert_eq!(fix_common("IGOR [FLAC (24bit)] {WEB, Lossless}"), "IGOR");
+        assert_eq!(fix_common("IGOR 【Mp3 / 320 kbps】 [ - ] ()"), "IGOR");
+        assert_eq!(fix_common("IGOR (Deluxe) [Mp3]"), "IGOR (Deluxe)");
+    }
+    #[test]
+    fn fix_common_2() {
+        assert_eq!(fix_common("IGOR (Mp3"), "IGOR");
+        assert_eq!(fix_common("IGOR [Mp3)"), "IGOR");
+        assert_eq!(fix_common("Title (Mp3 Remix)"), "Title (Remix)");
+        assert_eq!(fix_common("Title ( Remix )"), "Title (Remix)");
+        assert_eq!(fix_common("IGOR (Live [FLAC]"), "IGOR (Live");
+        assert_eq!(fix_common("Charlotte's Web (Opus 27) [CD]"), "Charlotte's Web (Opus 27)");
+    }
+    #[test]
+    fn fix_common_3() {
+        assert_eq!(fix_common("The 24-Bit Song"), "The 24-Bit Song");
+        assert_eq!(fix_common("The 24-Bit Song (24-bit) [16 bit FLAC]"), "The 24-Bit Song");
+    }
+    #[test]
+    fn fix_album_title_6() {
+        assert_eq!(fix_album_title("Prince - 1999 (1982) [FLAC]"), "Prince - 1999");
+        assert_eq!(fix_album_title("Dr. Dre - 2001!"), "Dr. Dre - 2001!");
+        // Known limitation: a trailing year that is also a protected title,
+        // such as Prince's "1999", is kept even when it is only the release
+        // year, since the default `YearRemover` cannot tell them apart.
+        assert_eq!(fix_album_title("Blink-182 - Enema of the State - 1999"), "Blink-182 - Enema of the State - 1999");
+        assert_eq!(fix_album_title("Blink-182 - Enema of the State - 2000"), "Blink-182 - Enema of the State");
+    }
+    #[test]
+    fn fix_artists_string_1() {
+        let actual = fix_artists_string("Drake, Future  &  Young Thug (2019)");
+        assert_eq!(actual, vec!["Drake", "Future", "Young Thug"]);
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn schema_1() {
+        use serde_json::{json, Value};
+
+        fn to_json<T: serde::Serialize>(value: T) -> Value {
+            serde_json::to_value(value).unwrap()
+        }
+
+        let schema: Value = serde_json::from_str(include_str!("../schema.json")).unwrap();
+        let explanation = explain("IGOR (2019) [Mp3]");
+        let rule = Rule {
+            name: "live".to_string(),
+            pattern: r"(?i)\(live\)".to_string(),
+            field: Field::Track,
+            replacement: " ".to_string(),
+            priority: 1,
+        };
+        let clustering = Deduplicator::default().cluster(vec![("Drake", "Jumpman")]);
+        let values: Vec<(&str, Value)> = vec![
+            ("Span", to_json(&explanation.applications[0].span)),
+            ("AudioFormat", to_json(AudioFormat::Flac)),
+            ("AudioSource", to_json(AudioSource::Vinyl)),
+            ("Explicitness", to_json(Explicitness::Clean)),
+            ("VersionType", to_json(VersionType::RadioEdit)),
+            ("Step", to_json(Step::EmptyBrackets)),
+            (
+                "CleanedMetadata",
+                to_json(parse_album_title("IGOR (2019) [Mp3]")),
+            ),
+            (
+                "CleanedMetadata",
+                to_json(parse_artists_string("Drake & Future")),
+            ),
+            (
+                "AlbumEdition",
+                to_json(parse_album_edition("Views (Deluxe Edition) [Explicit] CD2")),
+            ),
+            (
+                "ArtistTitle",
+                to_json(split_artist_and_title("Drake - Jumpman").unwrap()),
+            ),
+            (
+                "TrackCredits",
+                to_json(parse_track_credits("Song ft. X [Prod. by Z]")),
+            ),
+            ("NumberedTitle", to_json(parse_track_number("A1. Title"))),
+            (
+                "TrackVersion",
+                to_json(&parse_track_version("Song (Remastered 2009)").versions[0]),
+            ),
+            (
+                "VersionedTitle",
+                to_json(parse_track_version("Song (Skrillex Remix)")),
+            ),
+            (
+                "PathMetadata",
+                to_json(parse_path("Artist/Album/03 - Title.mp3")),
+            ),
+            (
+                "ReleaseInfo",
+                to_json(parse_release_name("Artist-Album-WEB-2019-GRP")),
+            ),
+            (
+                "Edit",
+                to_json(Edit {
+                    span: 0..4,
+                    replacement: " ".to_string(),
+                }),
+            ),
+            ("RuleApplication", to_json(&explanation.applications[0])),
+            ("Explanation", to_json(&explanation)),
+            ("Field", to_json(Field::All)),
+            ("Rule", to_json(&rule)),
+            ("RuleFile", json!({ "rules": [to_json(&rule)] })),
+            ("Cluster", to_json(&clustering.clusters[0])),
+            ("Clustering", to_json(&clustering)),
+            ("Deduplicator", to_json(Deduplicator::default())),
+            ("YearRemover", to_json(YearRemover::default())),
+            ("ArtistSplitter", to_json(ArtistSplitter::default())),
+        ];
+
+        let defs = schema["$defs"].as_object().unwrap();
+        for (name, value) in &values {
+            let mut def_schema = schema.clone();
+            def_schema["$ref"] = json!(format!("#/$defs/{}", name));
+            let compiled = jsonschema::JSONSchema::compile(&def_schema).unwrap();
+            if let Err(errors) = compiled.validate(value) {
+                let errors: Vec<String> = errors.map(|error| error.to_string()).collect();
+                panic!("{} {} does not match the schema: {:?}", name, value, errors);
+            }
+            // Fields that are missing from the schema are not caught by it.
+            if let Some(object) = value.as_object() {
+                let properties = defs[*name]["properties"].as_object().unwrap();
+                for field in object.keys() {
+                    assert!(
+                        properties.contains_key(field),
+                        "{}.{} is not in the schema",
+                        name,
+                        field
+                    );
+                }
+            }
+        }
+        for name in defs.keys() {
+            assert!(
+                values.iter().any(|(tested, _)| tested == name),
+                "{} is not tested",
+                name
+            );
+        }
+    }
 }
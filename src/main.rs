@@ -0,0 +1,441 @@
+//! A command-line tool to clean music metadata in bulk.
+//!
+//! Reads plain lines, CSV or JSON Lines from standard input or from files,
+//! cleans the chosen fields with [`fix_album_title`], [`fix_track_title`]
+//! or [`fix_artists_string`], and writes the results to standard output.
+//!
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process;
+
+use music_metadata_cleaner::{
+    fix_album_title, fix_artists_string, fix_track_title, Field, RuleSet,
+};
+
+const USAGE: &str = "\
+Usage: music-metadata-cleaner [OPTIONS] [FILE]...
+
+Cleans music metadata read from each FILE in order, or from standard input
+if no FILE is given, and writes the results to standard output. A FILE of
+`-` reads standard input.
+
+Options:
+  --format FORMAT     The input and output format: lines, csv or jsonl.
+                      Defaults to lines.
+  --field KIND        For the lines format, how to clean each line: album,
+                      track or artists. Defaults to track.
+  --column NAME=KIND  For the csv and jsonl formats, clean the column or
+                      field NAME as KIND. Can be given more than once.
+                      CSV columns can be given by header or by 0-based index.
+  --diff              Only write the rows that changed, as a `-` line with
+                      the original row followed by a `+` line with the
+                      cleaned row.
+  --rules FILE        Apply the custom rules in FILE, a TOML file or a
+                      `.json` file, before the built-in cleaning.
+  -h, --help          Print this help and exit.
+
+Cleaned artists are joined with \"; \" in the lines and csv formats, and
+written as an array of strings in the jsonl format.
+";
+
+/// Which of the crate's cleaning functions to apply to a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldKind {
+    Album,
+    Track,
+    Artists,
+}
+
+impl FieldKind {
+    fn parse(kind: &str) -> Result<FieldKind, String> {
+        match kind {
+            "album" => Ok(FieldKind::Album),
+            "track" => Ok(FieldKind::Track),
+            "artists" => Ok(FieldKind::Artists),
+            _ => Err(format!("unknown field kind `{}`", kind)),
+        }
+    }
+
+    fn field(self) -> Field {
+        match self {
+            FieldKind::Album => Field::Album,
+            FieldKind::Track => Field::Track,
+            FieldKind::Artists => Field::Artists,
+        }
+    }
+
+    /// Clean `dirty`, joining multiple artists with `"; "`.
+    fn clean(self, rules: &RuleSet, dirty: &str) -> String {
+        let dirty = rules.clean(self.field(), dirty);
+        match self {
+            FieldKind::Album => fix_album_title(&dirty),
+            FieldKind::Track => fix_track_title(&dirty),
+            FieldKind::Artists => fix_artists_string(&dirty).join("; "),
+        }
+    }
+
+    /// Clean `dirty` into a JSON value, keeping multiple artists as an array.
+    fn clean_json(self, rules: &RuleSet, dirty: &str) -> serde_json::Value {
+        match self {
+            FieldKind::Artists => fix_artists_string(&rules.clean(self.field(), dirty)).into(),
+            _ => self.clean(rules, dirty).into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Lines,
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug)]
+struct Options {
+    format: Format,
+    field: FieldKind,
+    columns: Vec<(String, FieldKind)>,
+    diff: bool,
+    rules: RuleSet,
+    files: Vec<String>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        format: Format::Lines,
+        field: FieldKind::Track,
+        columns: Vec::new(),
+        diff: false,
+        rules: RuleSet::default(),
+        files: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                process::exit(0);
+            }
+            "--format" => {
+                options.format = match value("--format")?.as_str() {
+                    "lines" => Format::Lines,
+                    "csv" => Format::Csv,
+                    "jsonl" => Format::JsonLines,
+                    other => return Err(format!("unknown format `{}`", other)),
+                }
+            }
+            "--field" => options.field = FieldKind::parse(&value("--field")?)?,
+            "--column" => {
+                let column = value("--column")?;
+                let mut parts = column.splitn(2, '=');
+                let name = parts.next().unwrap_or_default().to_string();
+                let kind = parts
+                    .next()
+                    .ok_or(format!("--column `{}` must look like NAME=KIND", column))?;
+                options.columns.push((name, FieldKind::parse(kind)?));
+            }
+            "--diff" => options.diff = true,
+            "--rules" => {
+                let path = value("--rules")?;
+                options.rules =
+                    RuleSet::from_file(&path).map_err(|error| format!("{}: {}", path, error))?;
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option `{}`", arg))
+            }
+            _ => options.files.push(arg),
+        }
+    }
+    if options.format != Format::Lines && options.columns.is_empty() {
+        return Err("the csv and jsonl formats need at least one --column".to_string());
+    }
+    Ok(options)
+}
+
+fn clean_lines<R: BufRead, W: Write>(
+    options: &Options,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        let cleaned = options.field.clean(&options.rules, &line);
+        if !options.diff {
+            writeln!(output, "{}", cleaned)?;
+        } else if cleaned != line {
+            writeln!(output, "-{}\n+{}", line, cleaned)?;
+        }
+    }
+    Ok(())
+}
+
+/// Format a single CSV row, including the line terminator.
+fn csv_row<I, T>(fields: I) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields)?;
+    Ok(writer.into_inner().map_err(|error| error.into_error())?)
+}
+
+/// Clean a CSV input. `first_headers` holds the headers of the first CSV
+/// input, which are only written once, and which later inputs must match.
+fn clean_csv<R: Read, W: Write>(
+    options: &Options,
+    input: R,
+    output: &mut W,
+    first_headers: &mut Option<csv::StringRecord>,
+) -> Result<(), Box<dyn Error>> {
+    let mut reader = csv::Reader::from_reader(input);
+    let headers = reader.headers()?.clone();
+    match first_headers {
+        Some(first) if *first != headers => {
+            return Err(format!(
+                "the CSV header `{}` does not match the first header `{}`",
+                headers.iter().collect::<Vec<_>>().join(","),
+                first.iter().collect::<Vec<_>>().join(","),
+            )
+            .into());
+        }
+        Some(_) => {}
+        None => {
+            if !options.diff {
+                output.write_all(&csv_row(&headers)?)?;
+            }
+            *first_headers = Some(headers.clone());
+        }
+    }
+    let mut columns = Vec::with_capacity(options.columns.len());
+    for (name, kind) in &options.columns {
+        let index = headers
+            .iter()
+            .position(|header| header == name)
+            .or_else(|| name.parse().ok().filter(|&index| index < headers.len()))
+            .ok_or(format!("no CSV column called `{}`", name))?;
+        columns.push((index, *kind));
+    }
+
+    for record in reader.records() {
+        let record = record?;
+        let mut cleaned: Vec<String> = record.iter().map(str::to_string).collect();
+        for &(index, kind) in &columns {
+            if let Some(value) = record.get(index) {
+                cleaned[index] = kind.clean(&options.rules, value);
+            }
+        }
+        if !options.diff {
+            output.write_all(&csv_row(&cleaned)?)?;
+        } else if record.iter().ne(cleaned.iter().map(String::as_str)) {
+            output.write_all(b"-")?;
+            output.write_all(&csv_row(&record)?)?;
+            output.write_all(b"+")?;
+            output.write_all(&csv_row(&cleaned)?)?;
+        }
+    }
+    Ok(())
+}
+
+fn clean_json_lines<R: BufRead, W: Write>(
+    options: &Options,
+    input: R,
+    output: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let original: serde_json::Value = serde_json::from_str(&line)
+            .map_err(|error| format!("line {}: {}", number + 1, error))?;
+        let mut cleaned = original.clone();
+        if let Some(object) = cleaned.as_object_mut() {
+            for (name, kind) in &options.columns {
+                if let Some(value) = object.get_mut(name) {
+                    if let Some(dirty) = value.as_str() {
+                        *value = kind.clean_json(&options.rules, dirty);
+                    }
+                }
+            }
+        }
+        if !options.diff {
+            writeln!(output, "{}", cleaned)?;
+        } else if cleaned != original {
+            writeln!(output, "-{}\n+{}", original, cleaned)?;
+        }
+    }
+    Ok(())
+}
+
+/// Clean one input. `csv_headers` is shared by all the inputs; see [`clean_csv`].
+fn clean<R: Read, W: Write>(
+    options: &Options,
+    input: R,
+    output: &mut W,
+    csv_headers: &mut Option<csv::StringRecord>,
+) -> Result<(), Box<dyn Error>> {
+    match options.format {
+        Format::Lines => clean_lines(options, BufReader::new(input), output)?,
+        Format::Csv => clean_csv(options, input, output, csv_headers)?,
+        Format::JsonLines => clean_json_lines(options, BufReader::new(input), output)?,
+    }
+    Ok(())
+}
+
+/// Clean every input in the order given, where `-` is `stdin`.
+/// With no inputs, only `stdin` is cleaned.
+fn clean_inputs<R: Read, W: Write>(
+    options: &Options,
+    mut stdin: R,
+    output: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    let mut csv_headers = None;
+    let stdin_only = ["-".to_string()];
+    let paths = if options.files.is_empty() {
+        &stdin_only[..]
+    } else {
+        &options.files[..]
+    };
+    for path in paths {
+        if path == "-" {
+            clean(options, &mut stdin, output, &mut csv_headers)?;
+            continue;
+        }
+        let file = File::open(path).map_err(|error| format!("{}: {}", path, error))?;
+        clean(options, file, output, &mut csv_headers)
+            .map_err(|error| format!("{}: {}", path, error))?;
+    }
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    clean_inputs(options, io::stdin().lock(), &mut output)?;
+    output.flush()?;
+    Ok(())
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("music-metadata-cleaner: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(error) = run(&options) {
+        eprintln!("music-metadata-cleaner: {}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn options(args: &[&str]) -> Options {
+        parse_args(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    fn run_on(args: &[&str], input: &str) -> String {
+        let mut output = Vec::new();
+        clean(&options(args), input.as_bytes(), &mut output, &mut None).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn clean_lines_1() {
+        let actual = run_on(&["--field", "album", "--diff"], "IGOR\nIGOR (2019) [Mp3]\n");
+        assert_eq!(actual, "-IGOR (2019) [Mp3]\n+IGOR\n");
+    }
+    #[test]
+    fn clean_csv_1() {
+        let input = "artist,title\n\"Drake, Future\",03. Jumpman [320]\nDrake,Jumpman\n";
+        let actual = run_on(
+            &[
+                "--format",
+                "csv",
+                "--column",
+                "artist=artists",
+                "--column",
+                "1=track",
+            ],
+            input,
+        );
+        assert_eq!(
+            actual,
+            "artist,title\nDrake; Future,Jumpman\nDrake,Jumpman\n"
+        );
+    }
+    #[test]
+    fn clean_csv_2() {
+        let options = options(&["--format", "csv", "--column", "title=track"]);
+        let mut output = Vec::new();
+        let mut headers = None;
+        for input in &["title\n03. A\n", "title\nB (2019)\n"] {
+            clean(&options, input.as_bytes(), &mut output, &mut headers).unwrap();
+        }
+        assert_eq!(String::from_utf8(output).unwrap(), "title\nA\nB\n");
+        let mut output = Vec::new();
+        let mismatched = clean(
+            &options,
+            "name,title\nC,D\n".as_bytes(),
+            &mut output,
+            &mut headers,
+        );
+        assert!(mismatched.is_err());
+    }
+    #[test]
+    fn clean_json_lines_1() {
+        let input = "{\"title\":\"03. Jumpman\",\"n\":1}\n{\"title\":\"Jumpman\",\"n\":2}\n";
+        let actual = run_on(
+            &["--format", "jsonl", "--column", "title=track", "--diff"],
+            input,
+        );
+        assert_eq!(
+            actual,
+            "-{\"title\":\"03. Jumpman\",\"n\":1}\n+{\"title\":\"Jumpman\",\"n\":1}\n"
+        );
+    }
+    #[test]
+    fn clean_json_lines_2() {
+        let input = "{\"artist\":\"Drake & Future\"}\n";
+        let actual = run_on(&["--format", "jsonl", "--column", "artist=artists"], input);
+        assert_eq!(actual, "{\"artist\":[\"Drake\",\"Future\"]}\n");
+    }
+    #[test]
+    fn clean_lines_2() {
+        let path = std::env::temp_dir().join("music-metadata-cleaner-rules.toml");
+        std::fs::write(
+            &path,
+            "[[rules]]\nname = \"free\"\npattern = '(?i)\\[free download\\]'\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+        let actual = run_on(&["--rules", path], "Jumpman [FREE DOWNLOAD] [Mp3]\n");
+        assert_eq!(actual, "Jumpman\n");
+    }
+    #[test]
+    fn clean_inputs_1() {
+        let path = std::env::temp_dir().join("music-metadata-cleaner-inputs.txt");
+        std::fs::write(&path, "A (2019)\n").unwrap();
+        let path = path.to_str().unwrap();
+        for (args, expected) in &[
+            (vec![path, "-"], "A\nB\n"),
+            (vec!["-", path], "B\nA\n"),
+            (vec![], "B\n"),
+        ] {
+            let mut output = Vec::new();
+            clean_inputs(&options(args), "B [Mp3]\n".as_bytes(), &mut output).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), *expected);
+        }
+    }
+    #[test]
+    fn parse_args_1() {
+        assert!(parse_args(vec!["--format".to_string(), "csv".to_string()].into_iter()).is_err());
+        assert!(parse_args(vec!["--field".to_string(), "song".to_string()].into_iter()).is_err());
+    }
+}
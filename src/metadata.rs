@@ -0,0 +1,266 @@
+//! Structured results that keep the annotations removed during cleaning.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::edition::remove_album_annotations;
+use crate::explain::apply_edits;
+use crate::track_number::remove_track_number;
+use crate::year::DEFAULT_YEAR_REMOVER;
+use crate::{
+    bitrate_annotation_edits, format_annotation_edits, normalize_unicode, remove_empty_brackets,
+    remove_redundant_whitespace, remove_upload_noise, remove_year_annotation, split_artists,
+    AlbumEdition, Edit, NumberedTitle, BITRATE_REGEX, BRACKETED_BITRATE_REGEX,
+    BRACKETED_FORMAT_REGEX, FORMAT_REGEX,
+};
+
+lazy_static! {
+    static ref DIGITS_REGEX: Regex = Regex::new(r"[0-9]+").unwrap();
+    static ref LABEL_REGEX: Regex = Regex::new(r"[[:alnum:]]+").unwrap();
+}
+
+/// An audio file format named in a metadata string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AudioFormat {
+    /// MPEG-1 Audio Layer III.
+    Mp3,
+    /// Free Lossless Audio Codec.
+    Flac,
+    /// Apple Lossless Audio Codec.
+    Alac,
+    /// Advanced Audio Coding.
+    Aac,
+    /// An MPEG-4 audio container, usually holding AAC or ALAC.
+    M4a,
+    /// An Ogg container, usually holding Vorbis.
+    Ogg,
+    /// The Opus codec.
+    Opus,
+    /// Uncompressed WAVE audio.
+    Wav,
+}
+
+impl AudioFormat {
+    pub(crate) fn from_label(label: &str) -> Option<AudioFormat> {
+        match label.to_lowercase().as_str() {
+            "mp3" => Some(AudioFormat::Mp3),
+            "flac" => Some(AudioFormat::Flac),
+            "alac" => Some(AudioFormat::Alac),
+            "aac" => Some(AudioFormat::Aac),
+            "m4a" => Some(AudioFormat::M4a),
+            "ogg" => Some(AudioFormat::Ogg),
+            "opus" => Some(AudioFormat::Opus),
+            "wav" => Some(AudioFormat::Wav),
+            _ => None,
+        }
+    }
+}
+
+/// The medium that a release was ripped from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AudioSource {
+    /// A digital download or stream, labeled `WEB`.
+    Web,
+    /// A compact disc, labeled `CD`.
+    Cd,
+    /// A vinyl record, labeled `Vinyl` or `Vinyl rip`.
+    Vinyl,
+}
+
+impl AudioSource {
+    pub(crate) fn from_label(label: &str) -> Option<AudioSource> {
+        match label.to_lowercase().as_str() {
+            "web" => Some(AudioSource::Web),
+            "cd" => Some(AudioSource::Cd),
+            "vinyl" | "vinylrip" => Some(AudioSource::Vinyl),
+            _ => None,
+        }
+    }
+}
+
+/// A cleaned metadata value, together with the annotations removed from it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CleanedMetadata<T = String> {
+    /// The cleaned value.
+    pub value: T,
+    /// The year from a year annotation such as `(2019)`.
+    pub year: Option<u16>,
+    /// The bitrate from a bitrate annotation such as `(320 kbps)`.
+    pub bitrate_kbps: Option<u32>,
+    /// The audio format from a format label such as `Mp3` or `[FLAC]`.
+    pub format: Option<AudioFormat>,
+    /// The source medium from a label such as `[WEB]` or `Vinyl rip`.
+    pub source: Option<AudioSource>,
+}
+
+/// Returns the first number inside a match of `regexes` in the text removed by `edits`.
+fn first_number<N: std::str::FromStr>(
+    regexes: &[&Regex],
+    dirty: &str,
+    edits: &[Edit],
+) -> Option<N> {
+    edits.iter().find_map(|edit| {
+        regexes
+            .iter()
+            .flat_map(|regex| regex.find_iter(&dirty[edit.span.clone()]))
+            .filter_map(|annotation| DIGITS_REGEX.find(annotation.as_str()))
+            .find_map(|digits| digits.as_str().parse().ok())
+    })
+}
+
+/// Returns the first label inside a match of `regexes` in the text removed
+/// by `edits` that `from_label` recognizes.
+///
+/// Labels are made of letters and digits only, so `Vinyl-rip` is seen as `vinylrip`.
+fn first_label<T>(
+    regexes: &[&Regex],
+    dirty: &str,
+    edits: &[Edit],
+    from_label: fn(&str) -> Option<T>,
+) -> Option<T> {
+    edits.iter().find_map(|edit| {
+        regexes
+            .iter()
+            .flat_map(|regex| regex.find_iter(&dirty[edit.span.clone()]))
+            .find_map(|annotation| {
+                let label: String = LABEL_REGEX
+                    .find_iter(annotation.as_str())
+                    .map(|word| word.as_str())
+                    .collect();
+                from_label(&label)
+            })
+    })
+}
+
+/// Applies [`crate::fix_common`] and keeps the annotations that it removes.
+///
+/// When a string contains more than one annotation of the same kind,
+/// the first one is kept.
+///
+pub fn parse_common(dirty: &str) -> CleanedMetadata {
+    let dirty = normalize_unicode(dirty);
+    let year = DEFAULT_YEAR_REMOVER.year(&dirty);
+    let dirty_1 = remove_year_annotation(&dirty);
+    let format_edits = format_annotation_edits(&dirty_1);
+    let format_labels = [&*FORMAT_REGEX, &*BRACKETED_FORMAT_REGEX];
+    let format = first_label(
+        &format_labels,
+        &dirty_1,
+        &format_edits,
+        AudioFormat::from_label,
+    );
+    let source = first_label(
+        &format_labels,
+        &dirty_1,
+        &format_edits,
+        AudioSource::from_label,
+    );
+    let dirty_2 = apply_edits(&dirty_1, &format_edits);
+    let bitrate_edits = bitrate_annotation_edits(&dirty_2);
+    let bitrate_labels = [&*BITRATE_REGEX, &*BRACKETED_BITRATE_REGEX];
+    let bitrate_kbps = first_number(&bitrate_labels, &dirty_2, &bitrate_edits);
+    let dirty_3 = apply_edits(&dirty_2, &bitrate_edits);
+    let dirty_4 = remove_empty_brackets(&dirty_3);
+    CleanedMetadata {
+        value: remove_redundant_whitespace(&dirty_4),
+        year,
+        bitrate_kbps,
+        format,
+        source,
+    }
+}
+
+/// Like [`crate::fix_album_title`], but keeps the removed annotations.
+///
+/// Edition, disc and explicitness annotations are removed but not kept;
+/// use [`crate::parse_album_edition`] for those.
+pub fn parse_album_title(dirty: &str) -> CleanedMetadata {
+    parse_common(&remove_album_annotations(
+        dirty,
+        &mut AlbumEdition::default(),
+    ))
+}
+
+/// Like [`crate::fix_track_title`], but keeps the removed annotations.
+///
+/// Track number prefixes are removed but not kept; use
+/// [`crate::parse_track_number`] for those.
+pub fn parse_track_title(dirty: &str) -> CleanedMetadata {
+    let title = remove_track_number(dirty, &mut NumberedTitle::default(), false);
+    parse_common(&remove_upload_noise(&normalize_unicode(title)))
+}
+
+/// Like [`crate::fix_artists_string`], but keeps the removed annotations.
+pub fn parse_artists_string(dirty: &str) -> CleanedMetadata<Vec<String>> {
+    let common = parse_common(dirty);
+    CleanedMetadata {
+        value: split_artists(&common.value),
+        year: common.year,
+        bitrate_kbps: common.bitrate_kbps,
+        format: common.format,
+        source: common.source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::*;
+
+    #[test]
+    fn parse_album_title_1() {
+        let actual = parse_album_title("Tyler, The Creator - IGOR (2019) [Mp3] (320 kbps)");
+        assert_eq!(actual.value, "Tyler, The Creator - IGOR");
+        assert_eq!(actual.year, Some(2019));
+        assert_eq!(actual.bitrate_kbps, Some(320));
+        assert_eq!(actual.format, Some(AudioFormat::Mp3));
+    }
+    #[test]
+    fn parse_album_title_2() {
+        let actual = parse_album_title("IGOR");
+        let expected = CleanedMetadata {
+            value: "IGOR".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(actual, expected);
+    }
+    #[test]
+    fn parse_album_title_3() {
+        let actual = parse_album_title("Charlotte's Web [FLAC] [WEB] (24bit 16-44.1) 320 CBR");
+        assert_eq!(actual.value, "Charlotte's Web");
+        assert_eq!(actual.bitrate_kbps, Some(320));
+        assert_eq!(actual.format, Some(AudioFormat::Flac));
+        assert_eq!(actual.source, Some(AudioSource::Web));
+    }
+    #[test]
+    fn parse_album_title_4() {
+        let actual = parse_album_title("Nocturnes, Opus 27 (Vinyl Rip) [V0, Hi-Res]");
+        assert_eq!(actual.value, "Nocturnes, Opus 27");
+        assert_eq!(actual.format, None);
+        assert_eq!(actual.source, Some(AudioSource::Vinyl));
+    }
+    #[test]
+    fn parse_artists_string_1() {
+        let actual = parse_artists_string("Drake & Future (2015)");
+        assert_eq!(actual.value, vec!["Drake", "Future"]);
+        assert_eq!(actual.year, Some(2015));
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_album_title_serde_1() {
+        let actual = parse_album_title("IGOR (2019) [FLAC] [WEB]");
+        let json = serde_json::to_string(&actual).unwrap();
+        assert_eq!(
+            json,
+            r#"{"value":"IGOR","year":2019,"bitrate_kbps":null,"format":"flac","source":"web"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<CleanedMetadata>(&json).unwrap(),
+            actual
+        );
+    }
+}
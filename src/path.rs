@@ -0,0 +1,193 @@
+//! Functions to infer metadata from the path of a music file.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::track_number::remove_track_number;
+use crate::{
+    artists_similarity, fix_album_title, fix_artists_string, fix_track_title,
+    split_artist_and_title, NumberedTitle,
+};
+
+/// How similar the artist in a file name must be to the artist directory
+/// to be split out of the file name; see [`crate::artists_similarity`].
+const ARTIST_DIRECTORY_SIMILARITY: f64 = 0.9;
+
+lazy_static! {
+    static ref EXTENSION_REGEX: Regex = Regex::new(r"^(.+)\.([[:alnum:]]{1,5})$").unwrap();
+    static ref DISC_DIRECTORY_REGEX: Regex =
+        Regex::new(r"(?i)^(?:cd|disc|disk)[[:space:]]*([0-9]{1,2})$").unwrap();
+    // Directories of compilations, whose files name their own artists.
+    static ref COMPILATION_ARTIST_REGEX: Regex =
+        Regex::new(r"(?i)^(?:various(?:[[:space:]]+artists)?|va|compilations?)$").unwrap();
+}
+
+/// Metadata inferred from the path of a music file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathMetadata {
+    /// The artists, cleaned with [`fix_artists_string`].
+    pub artists: Vec<String>,
+    /// The album title, cleaned with [`fix_album_title`].
+    pub album: Option<String>,
+    /// The track title, cleaned with [`fix_track_title`].
+    pub title: Option<String>,
+    /// The track number from the file name.
+    pub track_number: Option<u32>,
+    /// The disc number, from the file name or a `CD1`/`Disc 2` directory.
+    pub disc_number: Option<u32>,
+    /// The lowercase file extension, without the dot.
+    pub extension: Option<String>,
+}
+
+/// Infer the artist, album, track and disc numbers, title and extension
+/// from the path of a music file.
+///
+/// Paths are expected to look like `Artist/Album/03 - Title.mp3`, with
+/// any of these variations:
+/// - the file name may include the artist, as in `03 - Artist - Title.mp3`
+/// - the album directory may include the artist, as in `Artist - Album (2019)`
+/// - the album may be split into `CD1`, `Disc 2`, ... directories
+/// - the track number may use any prefix understood by [`crate::parse_track_number`],
+///   including a disc number, as in `1-05 Title.mp3`, or a vinyl side
+///   without a separator, as in `A1 Title.flac`
+///
+/// Both `/` and `\` are accepted as directory separators. Information in
+/// the file name takes precedence over information in the directories,
+/// except that under an artist directory, the file name only names an
+/// artist if it matches the directory, as in `Artist/Album/03 - Artist -
+/// Title.mp3`. Compilation directories such as `Various Artists` do not
+/// count as artist directories.
+///
+pub fn parse_path(path: &str) -> PathMetadata {
+    let mut metadata = PathMetadata::default();
+    let mut components: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty())
+        .collect();
+    let filename = match components.pop() {
+        Some(filename) => filename,
+        None => return metadata,
+    };
+
+    // File name: `[disc-]track - [artist - ]title.extension`, of which the
+    // artist and title are split once the directories are known.
+    let stem = match EXTENSION_REGEX.captures(filename) {
+        Some(caps) => {
+            metadata.extension = Some(caps[2].to_lowercase());
+            caps.get(1).unwrap().as_str()
+        }
+        None => filename,
+    };
+    let mut numbered = NumberedTitle::default();
+    let stem = remove_track_number(stem, &mut numbered, true);
+    metadata.track_number = numbered.track_number;
+    metadata.disc_number = numbered.disc_number;
+
+    // Directories: `[artist/]album[/disc]`
+    if let Some(caps) = components
+        .last()
+        .and_then(|dir| DISC_DIRECTORY_REGEX.captures(dir))
+    {
+        if metadata.disc_number.is_none() {
+            metadata.disc_number = caps[1].parse().ok();
+        }
+        components.pop();
+    }
+    let mut directory_artists = Vec::new();
+    if let Some(album) = components.pop() {
+        match split_artist_and_title(album) {
+            Some(split) => {
+                directory_artists = fix_artists_string(&split.artist);
+                metadata.album = Some(fix_album_title(&split.title));
+            }
+            None => metadata.album = Some(fix_album_title(album)),
+        }
+    }
+    if let Some(artist) = components.pop() {
+        if directory_artists.is_empty() {
+            directory_artists = fix_artists_string(artist);
+        }
+    }
+    if directory_artists
+        .iter()
+        .any(|artist| COMPILATION_ARTIST_REGEX.is_match(artist))
+    {
+        directory_artists.clear();
+    }
+
+    // File name: `[artist - ]title`. Under an artist directory, a dash only
+    // separates an artist that matches the directory, so that titles such
+    // as `Another Brick in the Wall - Part 2` are kept whole.
+    let split = split_artist_and_title(stem).filter(|split| {
+        directory_artists.is_empty()
+            || artists_similarity(&fix_artists_string(&split.artist), &directory_artists)
+                >= ARTIST_DIRECTORY_SIMILARITY
+    });
+    match split {
+        Some(split) => {
+            metadata.artists = fix_artists_string(&split.artist);
+            metadata.title = Some(fix_track_title(&split.title));
+        }
+        None => {
+            metadata.artists = directory_artists;
+            metadata.title = Some(fix_track_title(stem));
+        }
+    }
+
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::*;
+
+    #[test]
+    fn parse_path_1() {
+        let actual = parse_path("Artist/Album (2019)/03 - Artist - Title [320].mp3");
+        assert_eq!(actual.artists, vec!["Artist"]);
+        assert_eq!(actual.album.as_deref(), Some("Album"));
+        assert_eq!(actual.title.as_deref(), Some("Title"));
+        assert_eq!(actual.track_number, Some(3));
+        assert_eq!(actual.disc_number, None);
+        assert_eq!(actual.extension.as_deref(), Some("mp3"));
+    }
+    #[test]
+    fn parse_path_2() {
+        let actual = parse_path(r"D:\Music\Pink Floyd - The Wall [FLAC]\CD2\1-05 Hey You.FLAC");
+        assert_eq!(actual.artists, vec!["Pink Floyd"]);
+        assert_eq!(actual.album.as_deref(), Some("The Wall"));
+        assert_eq!(actual.title.as_deref(), Some("Hey You"));
+        assert_eq!(actual.track_number, Some(5));
+        assert_eq!(actual.disc_number, Some(1));
+        assert_eq!(actual.extension.as_deref(), Some("flac"));
+    }
+    #[test]
+    fn parse_path_3() {
+        let actual = parse_path("JAY-Z/The Black Album/99 Problems.mp3");
+        assert_eq!(actual.artists, vec!["JAY-Z"]);
+        assert_eq!(actual.album.as_deref(), Some("The Black Album"));
+        assert_eq!(actual.title.as_deref(), Some("99 Problems"));
+        assert_eq!(actual.track_number, None);
+    }
+    #[test]
+    fn parse_path_4() {
+        let actual = parse_path("Artist/Album [Vinyl]/B2 Title.flac");
+        assert_eq!(actual.title.as_deref(), Some("Title"));
+        assert_eq!(actual.track_number, Some(2));
+    }
+    #[test]
+    fn parse_path_5() {
+        let actual = parse_path("Pink Floyd/The Wall/05 - Another Brick in the Wall - Part 2.mp3");
+        assert_eq!(actual.artists, vec!["Pink Floyd"]);
+        assert_eq!(
+            actual.title.as_deref(),
+            Some("Another Brick in the Wall - Part 2")
+        );
+        let actual = parse_path("Various Artists/Now 42/03 - Drake - Jumpman.mp3");
+        assert_eq!(actual.artists, vec!["Drake"]);
+        assert_eq!(actual.title.as_deref(), Some("Jumpman"));
+        let actual = parse_path("03 - Drake - Jumpman.mp3");
+        assert_eq!(actual.artists, vec!["Drake"]);
+    }
+}
@@ -0,0 +1,324 @@
+//! A configurable pipeline of cleaning steps.
+//!
+//! [`crate::fix_common`] always applies the same steps in the same order.
+//! A [`Pipeline`] lets you choose which steps run, reorder them, and add
+//! your own steps by implementing [`Cleaner`].
+//!
+use std::borrow::Cow;
+
+use crate::explain::{diff_edits, explain_steps};
+use crate::unicode::normalize_unicode_edits;
+use crate::year::DEFAULT_YEAR_REMOVER;
+use crate::{
+    bitrate_annotation_edits, empty_brackets_edits, format_annotation_edits, normalize_unicode,
+    redundant_whitespace_edits, remove_bitrate_annotation, remove_empty_brackets,
+    remove_format_annotation, remove_redundant_whitespace, remove_upload_noise,
+    remove_year_annotation, title_case, Edit, Explanation, Field, RuleSet,
+};
+
+/// A single step that transforms a metadata string.
+pub trait Cleaner: Send + Sync {
+    /// A short, unique name that identifies this step in a pipeline.
+    fn name(&self) -> &str;
+
+    /// Clean the input string.
+    fn clean<'a>(&self, dirty: &'a str) -> Cow<'a, str>;
+
+    /// The replacements that [`Cleaner::clean`] makes to `dirty`, sorted
+    /// and without overlaps. Used by [`Pipeline::explain`].
+    ///
+    /// The default implementation returns a single edit that covers
+    /// everything between the common prefix and suffix of `dirty` and the
+    /// cleaned string. Override it to report each replacement separately.
+    fn edits(&self, dirty: &str) -> Vec<Edit> {
+        diff_edits(dirty, &self.clean(dirty))
+    }
+}
+
+/// The cleaning steps built into this crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Step {
+    /// Folds look-alike Unicode characters and normalizes to NFC.
+    UnicodeNormalization,
+    /// Removes year annotations such as `(2019)`.
+    ///
+    /// To protect more titles from this step, replace it with a
+    /// customized [`crate::YearRemover`].
+    YearAnnotation,
+    /// Removes audio format and quality labels such as `[FLAC]` or `Mp3`.
+    FormatAnnotation,
+    /// Removes bitrate annotations such as `(320 kbps)`.
+    BitrateAnnotation,
+    /// Removes brackets left empty by the other steps, such as `()` or `[ - ]`.
+    EmptyBrackets,
+    /// Collapses repeated whitespace and trims both ends.
+    RedundantWhitespace,
+    /// Removes upload noise such as `(Official Music Video)`, emoji and
+    /// hashtags.
+    ///
+    /// This step is not in [`Step::DEFAULT`]; see [`crate::remove_upload_noise`].
+    UploadNoise,
+    /// Title-cases strings in all caps, all lowercase or alternating case.
+    ///
+    /// This step is not in [`Step::DEFAULT`]; see [`crate::title_case`].
+    TitleCase,
+}
+
+impl Step {
+    /// The steps applied by [`crate::fix_common`], in order.
+    pub const DEFAULT: [Step; 6] = [
+        Step::UnicodeNormalization,
+        Step::YearAnnotation,
+        Step::FormatAnnotation,
+        Step::BitrateAnnotation,
+        Step::EmptyBrackets,
+        Step::RedundantWhitespace,
+    ];
+}
+
+impl Cleaner for Step {
+    fn name(&self) -> &str {
+        match self {
+            Step::UnicodeNormalization => "unicode_normalization",
+            Step::YearAnnotation => "year_annotation",
+            Step::FormatAnnotation => "format_annotation",
+            Step::BitrateAnnotation => "bitrate_annotation",
+            Step::EmptyBrackets => "empty_brackets",
+            Step::RedundantWhitespace => "redundant_whitespace",
+            Step::UploadNoise => "upload_noise",
+            Step::TitleCase => "title_case",
+        }
+    }
+
+    fn clean<'a>(&self, dirty: &'a str) -> Cow<'a, str> {
+        match self {
+            Step::UnicodeNormalization => normalize_unicode(dirty),
+            Step::YearAnnotation => remove_year_annotation(dirty),
+            Step::FormatAnnotation => remove_format_annotation(dirty),
+            Step::BitrateAnnotation => remove_bitrate_annotation(dirty),
+            Step::EmptyBrackets => remove_empty_brackets(dirty),
+            Step::RedundantWhitespace => Cow::Owned(remove_redundant_whitespace(dirty)),
+            Step::UploadNoise => Cow::Owned(remove_upload_noise(dirty)),
+            Step::TitleCase => Cow::Owned(title_case(dirty)),
+        }
+    }
+
+    fn edits(&self, dirty: &str) -> Vec<Edit> {
+        match self {
+            Step::UnicodeNormalization => normalize_unicode_edits(dirty),
+            Step::YearAnnotation => DEFAULT_YEAR_REMOVER.edits(dirty),
+            Step::FormatAnnotation => format_annotation_edits(dirty),
+            Step::BitrateAnnotation => bitrate_annotation_edits(dirty),
+            Step::EmptyBrackets => empty_brackets_edits(dirty),
+            Step::RedundantWhitespace => redundant_whitespace_edits(dirty),
+            Step::UploadNoise => diff_edits(dirty, &remove_upload_noise(dirty)),
+            Step::TitleCase => diff_edits(dirty, &title_case(dirty)),
+        }
+    }
+}
+
+/// An ordered list of [`Cleaner`] steps.
+///
+/// The default pipeline applies the same steps as [`crate::fix_common`].
+pub struct Pipeline {
+    steps: Vec<Box<dyn Cleaner>>,
+}
+
+impl Pipeline {
+    /// Start building a pipeline with no steps.
+    pub fn builder() -> PipelineBuilder {
+        PipelineBuilder { steps: Vec::new() }
+    }
+
+    /// The names of the steps in this pipeline, in the order they are applied.
+    pub fn step_names(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.name()).collect()
+    }
+
+    /// Apply every step of this pipeline to `dirty`, in order, and record
+    /// which step made each replacement.
+    pub fn explain(&self, dirty: &str) -> Explanation {
+        explain_steps(&self.steps, dirty)
+    }
+
+    /// Apply every step of this pipeline to `dirty`, in order.
+    pub fn clean(&self, dirty: &str) -> String {
+        let mut current = dirty.to_string();
+        for step in &self.steps {
+            if let Cow::Owned(cleaned) = step.clean(&current) {
+                current = cleaned;
+            }
+        }
+        current
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        PipelineBuilder::default().build()
+    }
+}
+
+impl Cleaner for Pipeline {
+    fn name(&self) -> &str {
+        "pipeline"
+    }
+
+    fn clean<'a>(&self, dirty: &'a str) -> Cow<'a, str> {
+        Cow::Owned(Pipeline::clean(self, dirty))
+    }
+}
+
+/// Builds a [`Pipeline`].
+///
+/// `PipelineBuilder::default()` starts with the steps in [`Step::DEFAULT`],
+/// while [`Pipeline::builder`] starts with no steps.
+pub struct PipelineBuilder {
+    steps: Vec<Box<dyn Cleaner>>,
+}
+
+impl PipelineBuilder {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.steps.iter().position(|step| step.name() == name)
+    }
+
+    /// Append a step to the end of the pipeline.
+    pub fn step<C: Cleaner + 'static>(mut self, step: C) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Insert a step right before the step called `name`.
+    ///
+    /// If there is no step called `name`, the step is appended to the end.
+    pub fn step_before<C: Cleaner + 'static>(mut self, name: &str, step: C) -> Self {
+        let index = self.position(name).unwrap_or(self.steps.len());
+        self.steps.insert(index, Box::new(step));
+        self
+    }
+
+    /// Insert a step right after the step called `name`.
+    ///
+    /// If there is no step called `name`, the step is appended to the end.
+    pub fn step_after<C: Cleaner + 'static>(mut self, name: &str, step: C) -> Self {
+        let index = self
+            .position(name)
+            .map_or(self.steps.len(), |index| index + 1);
+        self.steps.insert(index, Box::new(step));
+        self
+    }
+
+    /// Replace the step called `name` with `step`.
+    ///
+    /// If there is no step called `name`, the step is appended to the end.
+    pub fn replace<C: Cleaner + 'static>(mut self, name: &str, step: C) -> Self {
+        match self.position(name) {
+            Some(index) => self.steps[index] = Box::new(step),
+            None => self.steps.push(Box::new(step)),
+        }
+        self
+    }
+
+    /// Insert the custom rules from `rules` that apply to `field`, in
+    /// order of priority, right after the `unicode_normalization` step.
+    ///
+    /// If there is no `unicode_normalization` step, the rules are inserted
+    /// at the start, so that the built-in steps can clean up after them.
+    pub fn rules(mut self, rules: &RuleSet, field: Field) -> Self {
+        let index = self
+            .position(Step::UnicodeNormalization.name())
+            .map_or(0, |index| index + 1);
+        self.steps.splice(index..index, rules.steps(field));
+        self
+    }
+
+    /// Remove every step called `name`.
+    pub fn without(mut self, name: &str) -> Self {
+        self.steps.retain(|step| step.name() != name);
+        self
+    }
+
+    /// Finish building the pipeline.
+    pub fn build(self) -> Pipeline {
+        Pipeline { steps: self.steps }
+    }
+}
+
+impl Default for PipelineBuilder {
+    fn default() -> Self {
+        Step::DEFAULT
+            .iter()
+            .fold(Pipeline::builder(), |builder, &step| builder.step(step))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::*;
+    use crate::*;
+
+    struct Uppercase;
+
+    impl Cleaner for Uppercase {
+        fn name(&self) -> &str {
+            "uppercase"
+        }
+
+        fn clean<'a>(&self, dirty: &'a str) -> Cow<'a, str> {
+            Cow::Owned(dirty.to_uppercase())
+        }
+    }
+
+    #[test]
+    fn pipeline_default_1() {
+        let dirty = "Tyler, The Creator - IGOR (2019) [Mp3] (320 kbps)";
+        assert_eq!(Pipeline::default().clean(dirty), fix_common(dirty));
+    }
+    #[test]
+    fn pipeline_builder_1() {
+        let pipeline = PipelineBuilder::default()
+            .without("unicode_normalization")
+            .without("year_annotation")
+            .step_before("redundant_whitespace", Uppercase)
+            .build();
+        assert_eq!(
+            pipeline.step_names(),
+            vec![
+                "format_annotation",
+                "bitrate_annotation",
+                "empty_brackets",
+                "uppercase",
+                "redundant_whitespace"
+            ]
+        );
+        let actual = pipeline.clean("Tyler, The Creator - IGOR (2019) Mp3 (320 kbps)");
+        assert_eq!(actual, "TYLER, THE CREATOR - IGOR (2019)");
+    }
+    #[test]
+    fn pipeline_title_case_1() {
+        let pipeline = PipelineBuilder::default().step(Step::TitleCase).build();
+        let actual = pipeline.clean("TYLER, THE CREATOR - IGOR (2019) [MP3]");
+        assert_eq!(actual, "Tyler, The Creator - IGOR");
+    }
+    #[test]
+    fn pipeline_builder_3() {
+        let pipeline = PipelineBuilder::default()
+            .replace("year_annotation", YearRemover::default().protect("2046"))
+            .build();
+        assert_eq!(pipeline.step_names()[1], "year_annotation");
+        assert_eq!(
+            pipeline.clean("Wong Kar-wai - 2046 (2004)"),
+            "Wong Kar-wai - 2046"
+        );
+    }
+    #[test]
+    fn pipeline_builder_2() {
+        let pipeline = Pipeline::builder()
+            .step(Step::RedundantWhitespace)
+            .step_after("redundant_whitespace", Uppercase)
+            .build();
+        assert_eq!(pipeline.clean("  igor   (2019) "), "IGOR (2019)");
+    }
+}
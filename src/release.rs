@@ -0,0 +1,310 @@
+//! A parser for torrent and scene release names.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::brackets::{bracket_groups, is_noise};
+use crate::year::BRACKETED_YEAR_REGEX;
+use crate::{
+    normalize_unicode, parse_album_title, split_artist_and_title, AudioFormat, AudioSource,
+    BITRATE_REGEX, BRACKETED_BITRATE_REGEX, BRACKETED_FORMAT_REGEX, FORMAT_REGEX,
+};
+
+lazy_static! {
+    static ref YEAR_TOKEN_REGEX: Regex = Regex::new(r"^(?:19|20)[0-9]{2}$").unwrap();
+    // Catalog numbers are written in uppercase without spaces, as in
+    // `CAT123`, `WARPCD92`, `ABC-123` or `88883716862`.
+    static ref CATALOG_NUMBER_REGEX: Regex = Regex::new(r"^[A-Z0-9]+(?:[-_.][A-Z0-9]+)*$").unwrap();
+    // Disc and volume numbers, as in `CD2`, `DISC-1` or `VOL.2`.
+    static ref DISC_TOKEN_REGEX: Regex = Regex::new(r"(?i)^(?:cd|disc|disk|vol|volume|pt|part)[-_.]?[0-9]{1,2}$").unwrap();
+    // A release group after the last bracket, as in `Album [FLAC]-GROUP`.
+    static ref BRACKETED_GROUP_REGEX: Regex = Regex::new(r"[\)\]\}]-([[:alnum:]_]+)$").unwrap();
+}
+
+/// The release info parsed out of a torrent or scene release name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReleaseInfo {
+    /// The cleaned artist, if the release name has one.
+    pub artist: Option<String>,
+    /// The album title, cleaned with [`crate::fix_album_title`].
+    pub album: String,
+    /// The release year.
+    pub year: Option<u16>,
+    /// The audio format, such as `FLAC`.
+    pub format: Option<AudioFormat>,
+    /// The source medium, such as `WEB` or `CD`.
+    pub source: Option<AudioSource>,
+    /// The bitrate, such as `320`.
+    pub bitrate_kbps: Option<u32>,
+    /// The catalog number, as in `(CAT123)` or `{CAT123}`.
+    pub catalog_number: Option<String>,
+    /// The record label, as in `{Label}`.
+    pub label: Option<String>,
+    /// The release group, as in `-GROUP` at the end of the name.
+    pub group: Option<String>,
+    /// Other scene tags, such as `REPACK`, `PROPER` or `24BIT`, as written.
+    pub tags: Vec<String>,
+}
+
+/// Returns `true` if `token` looks like a catalog number rather than a
+/// year, a disc number, or a format, source or bitrate label.
+fn is_catalog_number(token: &str) -> bool {
+    token.len() >= 4
+        && token.chars().any(|c| c.is_ascii_digit())
+        && CATALOG_NUMBER_REGEX.is_match(token)
+        && !DISC_TOKEN_REGEX.is_match(token)
+        && !FORMAT_REGEX.is_match(token)
+        && !BRACKETED_FORMAT_REGEX.is_match(token)
+        && !token.split(['-', '_', '.']).any(|part| {
+            YEAR_TOKEN_REGEX.is_match(part)
+                || scene_source(part).is_some()
+                || (BRACKETED_BITRATE_REGEX.is_match(part) && part.len() == 3)
+        })
+}
+
+/// Returns `true` if `content` is only format, source, bitrate or year
+/// labels, as in `{FLAC}` or `{WEB 320}`, so that it is not a record label.
+fn is_format_noise(content: &str) -> bool {
+    let labels = [
+        &*FORMAT_REGEX,
+        &*BRACKETED_FORMAT_REGEX,
+        &*BITRATE_REGEX,
+        &*BRACKETED_BITRATE_REGEX,
+        &*BRACKETED_YEAR_REGEX,
+    ];
+    is_noise(content, &labels)
+}
+
+/// The source medium of a scene source tag.
+fn scene_source(token: &str) -> Option<AudioSource> {
+    match token.to_uppercase().as_str() {
+        "WEB" => Some(AudioSource::Web),
+        "CD" | "CDDA" | "CDR" | "CDM" | "CDS" => Some(AudioSource::Cd),
+        "VINYL" | "VLS" => Some(AudioSource::Vinyl),
+        _ => None,
+    }
+}
+
+/// Scene names use underscores or dots instead of spaces.
+fn scene_words(token: &str) -> String {
+    token.replace(['_', '.'], " ").trim().to_string()
+}
+
+/// Parse a scene name such as `Artist-Album-(CAT123)-WEB-2019-GROUP` into
+/// the `Artist - Album` part and the release info.
+///
+/// Returns `None` if the name is made only of hyphens.
+fn parse_scene_name(dirty: &str, info: &mut ReleaseInfo) -> Option<String> {
+    let mut tokens: Vec<&str> = dirty.split('-').filter(|token| !token.is_empty()).collect();
+    if tokens.is_empty() {
+        return None;
+    }
+    if tokens.len() > 2 {
+        let last = tokens[tokens.len() - 1];
+        if !YEAR_TOKEN_REGEX.is_match(last) && scene_source(last).is_none() {
+            info.group = Some(last.to_string());
+            tokens.pop();
+        }
+    }
+    let mut name = scene_words(tokens[0]);
+    for (index, token) in tokens.iter().enumerate().skip(1) {
+        let bracketed = token.starts_with('(') && token.ends_with(')') && token.len() > 2;
+        let inner = if bracketed {
+            &token[1..token.len() - 1]
+        } else {
+            token
+        };
+        if index == 1 {
+            name.push_str(" - ");
+            name.push_str(&scene_words(token));
+        } else if YEAR_TOKEN_REGEX.is_match(inner) {
+            info.year = info.year.or_else(|| inner.parse().ok());
+        } else if let Some(source) = scene_source(inner) {
+            info.source = info.source.or(Some(source));
+        } else if let Some(format) = AudioFormat::from_label(inner) {
+            info.format = info.format.or(Some(format));
+        } else if BRACKETED_BITRATE_REGEX.is_match(inner) && inner.len() == 3 {
+            info.bitrate_kbps = info.bitrate_kbps.or_else(|| inner.parse().ok());
+        } else if info.catalog_number.is_none() && is_catalog_number(inner) {
+            info.catalog_number = Some(inner.to_string());
+        } else if bracketed {
+            name.push_str(&format!(" ({})", scene_words(inner)));
+        } else {
+            info.tags.push(inner.to_string());
+        }
+    }
+    Some(name)
+}
+
+/// Parse a torrent or scene release name into the cleaned album title and
+/// the release info around it.
+///
+/// Two styles of names are understood:
+/// - scene names without spaces, such as
+///   `Artist-Album-(CAT123)-WEB-2019-GROUP` or
+///   `Artist_Name-Album_Name-24BIT-WEB-FLAC-2019-GROUP`, where words are
+///   joined with underscores or dots, and the parts are joined with hyphens
+/// - torrent names, such as `Artist - Album (2019) [FLAC] [24-96] {Label}`,
+///   where a catalog number or a record label is written in curly brackets
+///   and a release group may follow the last bracket, as in `[FLAC]-GROUP`;
+///   curly brackets that hold only format labels, as in `{FLAC}`, are not
+///   a record label
+///
+/// Bracketed catalog numbers, years, formats, bitrates and sources are
+/// recognized in both styles. The album title is cleaned with
+/// [`crate::fix_album_title`], and the artist with [`crate::fix_common`].
+///
+/// Scene names cannot tell a hyphen in a name from a separator, so
+/// `Jay-Z-Album-2019-GROUP` is read as the artist `Jay`.
+///
+pub fn parse_release_name(dirty: &str) -> ReleaseInfo {
+    let mut info = ReleaseInfo::default();
+    let dirty = normalize_unicode(dirty.trim());
+    let scene_name = if !dirty.contains(char::is_whitespace) && dirty.contains('-') {
+        parse_scene_name(&dirty, &mut info)
+    } else {
+        None
+    };
+    let mut name = scene_name.unwrap_or_else(|| dirty.to_string());
+
+    if let Some(caps) = BRACKETED_GROUP_REGEX.captures(&name) {
+        info.group = Some(caps[1].to_string());
+        let start = caps.get(1).unwrap().start() - 1;
+        name.truncate(start);
+    }
+
+    let mut removed: Vec<std::ops::Range<usize>> = Vec::new();
+    for group in bracket_groups(&name) {
+        let content = name[group.content.clone()].trim();
+        if is_catalog_number(content) && info.catalog_number.is_none() {
+            info.catalog_number = Some(content.to_string());
+        } else if name[group.span.clone()].starts_with('{')
+            && !content.is_empty()
+            && !is_format_noise(content)
+        {
+            if info.label.is_none() {
+                info.label = Some(content.to_string());
+            }
+        } else {
+            continue;
+        }
+        removed.retain(|span| span.start < group.span.start || group.span.end < span.end);
+        removed.push(group.span);
+    }
+    removed.sort_by_key(|span| std::cmp::Reverse(span.start));
+    for span in removed {
+        name.replace_range(span, " ");
+    }
+
+    let cleaned = parse_album_title(&name);
+    info.year = info.year.or(cleaned.year);
+    info.format = info.format.or(cleaned.format);
+    info.source = info.source.or(cleaned.source);
+    info.bitrate_kbps = info.bitrate_kbps.or(cleaned.bitrate_kbps);
+    match split_artist_and_title(&cleaned.value) {
+        Some(split) => {
+            info.artist = Some(split.artist);
+            info.album = split.title;
+        }
+        None => info.album = cleaned.value,
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::release::*;
+
+    #[test]
+    fn parse_release_name_1() {
+        let actual = parse_release_name("Artist-Album-(CAT123)-WEB-2019-GROUP");
+        let expected = ReleaseInfo {
+            artist: Some("Artist".to_string()),
+            album: "Album".to_string(),
+            year: Some(2019),
+            source: Some(AudioSource::Web),
+            catalog_number: Some("CAT123".to_string()),
+            group: Some("GROUP".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(actual, expected);
+    }
+    #[test]
+    fn parse_release_name_2() {
+        let actual =
+            parse_release_name("Daft_Punk-Random_Access_Memories-24BIT-WEB-FLAC-REPACK-2013-GRP");
+        assert_eq!(actual.artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(actual.album, "Random Access Memories");
+        assert_eq!(actual.format, Some(AudioFormat::Flac));
+        assert_eq!(actual.year, Some(2013));
+        assert_eq!(actual.tags, vec!["24BIT", "REPACK"]);
+        assert_eq!(actual.group.as_deref(), Some("GRP"));
+    }
+    #[test]
+    fn parse_release_name_3() {
+        let actual = parse_release_name("Artist - Album (2019) [FLAC] [24-96] {Label}");
+        let expected = ReleaseInfo {
+            artist: Some("Artist".to_string()),
+            album: "Album".to_string(),
+            year: Some(2019),
+            format: Some(AudioFormat::Flac),
+            label: Some("Label".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(actual, expected);
+    }
+    #[test]
+    fn parse_release_name_4() {
+        let actual = parse_release_name("Artist - Album (Deluxe Edition) {WARPCD92} [320]-GROUP");
+        assert_eq!(actual.album, "Album");
+        assert_eq!(actual.bitrate_kbps, Some(320));
+        assert_eq!(actual.catalog_number.as_deref(), Some("WARPCD92"));
+        assert_eq!(actual.group.as_deref(), Some("GROUP"));
+        let actual = parse_release_name("Taylor_Swift-1989-WEB-2014-GRP");
+        assert_eq!(actual.album, "1989");
+        assert_eq!(actual.year, Some(2014));
+        let actual = parse_release_name("Album [CD2]");
+        assert_eq!(actual.artist, None);
+        assert_eq!(actual.album, "Album");
+        assert_eq!(actual.catalog_number, None);
+    }
+    #[test]
+    fn parse_release_name_5() {
+        for dirty in &["-", "---", ""] {
+            let actual = parse_release_name(dirty);
+            assert_eq!(actual.artist, None);
+            assert_eq!(actual.group, None);
+        }
+    }
+    #[test]
+    fn parse_release_name_6() {
+        for dirty in &[
+            "Artist - Album [WEB 320]",
+            "Artist - Album (2019 REMASTER)",
+            "Artist - Album (VOL 2)",
+            "Artist - Album (VOL.2)",
+            "Artist - Album [WEB-320]",
+            "Artist - Album [CD-2019]",
+        ] {
+            assert_eq!(parse_release_name(dirty).catalog_number, None, "{}", dirty);
+        }
+        let actual = parse_release_name("Artist - Album [ABC-123]");
+        assert_eq!(actual.catalog_number.as_deref(), Some("ABC-123"));
+    }
+    #[test]
+    fn parse_release_name_7() {
+        let actual = parse_release_name("Artist - Album {FLAC}");
+        assert_eq!(actual.album, "Album");
+        assert_eq!(actual.label, None);
+        assert_eq!(actual.format, Some(AudioFormat::Flac));
+        let actual = parse_release_name("Artist - Album {WEB} {Label}");
+        assert_eq!(actual.album, "Album");
+        assert_eq!(actual.label.as_deref(), Some("Label"));
+        assert_eq!(actual.source, Some(AudioSource::Web));
+        let actual = parse_release_name("Artist - Album {320 kbps} {2019}");
+        assert_eq!(actual.label, None);
+        assert_eq!(actual.bitrate_kbps, Some(320));
+        assert_eq!(actual.year, Some(2019));
+    }
+}
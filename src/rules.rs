@@ -0,0 +1,324 @@
+//! Custom removal and extraction rules, loaded at runtime.
+//!
+//! A rule file lists regular expressions to replace in one or all fields.
+//! In TOML:
+//!
+//! ```toml
+//! [[rules]]
+//! name = "free_download"
+//! pattern = '(?i)\[free download\]'
+//! field = "track"
+//! priority = 10
+//!
+//! [[rules]]
+//! name = "official_video"
+//! pattern = '(?i)\(official video\)'
+//! ```
+//!
+//! Or in JSON, as `{"rules": [{"name": "free_download", ...}]}`.
+//!
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use regex::Regex;
+
+use crate::explain::apply_edits;
+use crate::{Cleaner, Edit};
+
+/// The kind of metadata field that a [`Rule`] applies to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Field {
+    /// Album titles.
+    Album,
+    /// Track titles.
+    Track,
+    /// Artist strings.
+    Artists,
+    /// Every field.
+    #[default]
+    All,
+}
+
+impl Field {
+    /// Returns `true` if a rule for this field applies to `field`.
+    pub fn applies_to(self, field: Field) -> bool {
+        self == Field::All || field == Field::All || self == field
+    }
+}
+
+/// A custom rule that replaces every match of a regular expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    /// A short, unique name, reported by [`crate::Pipeline::explain`].
+    pub name: String,
+    /// The regular expression to match, in the syntax of the `regex` crate.
+    pub pattern: String,
+    /// The field that this rule applies to. Defaults to [`Field::All`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub field: Field,
+    /// The text that replaces each match. Defaults to a single space, like
+    /// the built-in rules.
+    ///
+    /// Capture groups can be referred to as `$1` or `$name`, so a rule can
+    /// extract part of a match, as in the pattern `\(prod\. (.+)\)` with the
+    /// replacement `$1`.
+    #[cfg_attr(feature = "serde", serde(default = "default_replacement"))]
+    pub replacement: String,
+    /// Rules with a higher priority run first. Defaults to 0.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub priority: i32,
+}
+
+#[cfg(feature = "serde")]
+fn default_replacement() -> String {
+    " ".to_string()
+}
+
+impl Rule {
+    /// A rule for every field that replaces `pattern` with a space, with priority 0.
+    pub fn new(name: &str, pattern: &str) -> Rule {
+        Rule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            field: Field::All,
+            replacement: " ".to_string(),
+            priority: 0,
+        }
+    }
+}
+
+/// An error in a rule file.
+#[derive(Debug)]
+pub enum RuleError {
+    /// The rule file could not be read.
+    Io(std::io::Error),
+    /// The rule file is not valid TOML or JSON, or does not describe rules.
+    Syntax(String),
+    /// A rule's pattern is not a valid regular expression.
+    Pattern {
+        /// The name of the rule.
+        rule: String,
+        /// Why the pattern is invalid.
+        error: regex::Error,
+    },
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Io(error) => write!(f, "could not read rules: {}", error),
+            RuleError::Syntax(message) => write!(f, "invalid rules: {}", message),
+            RuleError::Pattern { rule, error } => {
+                write!(f, "invalid pattern in rule `{}`: {}", rule, error)
+            }
+        }
+    }
+}
+
+impl Error for RuleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RuleError::Io(error) => Some(error),
+            RuleError::Syntax(_) => None,
+            RuleError::Pattern { error, .. } => Some(error),
+        }
+    }
+}
+
+/// A [`Rule`] with its compiled pattern, as a step of a [`crate::Pipeline`].
+#[derive(Clone, Debug)]
+struct CompiledRule {
+    rule: Rule,
+    regex: Regex,
+}
+
+impl Cleaner for CompiledRule {
+    fn name(&self) -> &str {
+        &self.rule.name
+    }
+
+    fn clean<'a>(&self, dirty: &'a str) -> Cow<'a, str> {
+        self.regex
+            .replace_all(dirty, self.rule.replacement.as_str())
+    }
+
+    fn edits(&self, dirty: &str) -> Vec<Edit> {
+        self.regex
+            .captures_iter(dirty)
+            .map(|caps| {
+                let mut replacement = String::new();
+                caps.expand(&self.rule.replacement, &mut replacement);
+                Edit {
+                    span: caps.get(0).unwrap().range(),
+                    replacement,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The file format of a set of rules.
+#[cfg(feature = "config")]
+#[derive(serde::Deserialize)]
+struct RuleFile {
+    rules: Vec<Rule>,
+}
+
+/// A set of custom rules, sorted from the highest priority to the lowest.
+///
+/// Add the rules to a pipeline with [`crate::PipelineBuilder::rules`], or
+/// apply them on their own with [`RuleSet::clean`].
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleSet {
+    /// Compile `rules`. Rules with the same priority keep their order.
+    pub fn new(rules: Vec<Rule>) -> Result<RuleSet, RuleError> {
+        let mut compiled = Vec::with_capacity(rules.len());
+        for rule in rules {
+            let regex = Regex::new(&rule.pattern).map_err(|error| RuleError::Pattern {
+                rule: rule.name.clone(),
+                error,
+            })?;
+            compiled.push(CompiledRule { rule, regex });
+        }
+        compiled.sort_by_key(|compiled| std::cmp::Reverse(compiled.rule.priority));
+        Ok(RuleSet { rules: compiled })
+    }
+
+    /// Load rules from a TOML string with a `[[rules]]` table per rule.
+    #[cfg(feature = "config")]
+    pub fn from_toml(config: &str) -> Result<RuleSet, RuleError> {
+        let file: RuleFile =
+            toml::from_str(config).map_err(|error| RuleError::Syntax(error.to_string()))?;
+        RuleSet::new(file.rules)
+    }
+
+    /// Load rules from a JSON string, as `{"rules": [...]}`.
+    #[cfg(feature = "config")]
+    pub fn from_json(config: &str) -> Result<RuleSet, RuleError> {
+        let file: RuleFile =
+            serde_json::from_str(config).map_err(|error| RuleError::Syntax(error.to_string()))?;
+        RuleSet::new(file.rules)
+    }
+
+    /// Load rules from a file. Files ending in `.json` are read as JSON,
+    /// and all other files as TOML.
+    #[cfg(feature = "config")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<RuleSet, RuleError> {
+        let path = path.as_ref();
+        let config = std::fs::read_to_string(path).map_err(RuleError::Io)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => RuleSet::from_json(&config),
+            _ => RuleSet::from_toml(&config),
+        }
+    }
+
+    /// The rules, from the highest priority to the lowest.
+    pub fn rules(&self) -> impl Iterator<Item = &Rule> {
+        self.rules.iter().map(|compiled| &compiled.rule)
+    }
+
+    /// The compiled rules that apply to `field`, as pipeline steps.
+    pub(crate) fn steps(&self, field: Field) -> impl Iterator<Item = Box<dyn Cleaner>> + '_ {
+        self.rules
+            .iter()
+            .filter(move |compiled| compiled.rule.field.applies_to(field))
+            .map(|compiled| Box::new(compiled.clone()) as Box<dyn Cleaner>)
+    }
+
+    /// Apply the rules that apply to `field` to `dirty`, in order of priority.
+    pub fn clean(&self, field: Field, dirty: &str) -> String {
+        let mut current = dirty.to_string();
+        for step in self.steps(field) {
+            let edits = step.edits(&current);
+            if !edits.is_empty() {
+                current = apply_edits(&current, &edits);
+            }
+        }
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::*;
+    use crate::*;
+
+    fn rule_set() -> RuleSet {
+        let mut free = Rule::new("free_download", r"(?i)\[free download\]");
+        free.field = Field::Track;
+        let mut producer = Rule::new("producer", r"\(prod\. (.+)\)");
+        producer.replacement = "$1".to_string();
+        producer.priority = 10;
+        RuleSet::new(vec![free, producer]).unwrap()
+    }
+
+    #[test]
+    fn rule_set_1() {
+        let rules = rule_set();
+        let names: Vec<_> = rules.rules().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, vec!["producer", "free_download"]);
+        assert_eq!(rules.clean(Field::Track, "Song [FREE DOWNLOAD]"), "Song  ");
+        assert_eq!(
+            rules.clean(Field::Album, "Song [FREE DOWNLOAD]"),
+            "Song [FREE DOWNLOAD]"
+        );
+        assert_eq!(rules.clean(Field::Album, "(prod. Metro)"), "Metro");
+    }
+    #[test]
+    fn rule_set_2() {
+        let error = RuleSet::new(vec![Rule::new("broken", "(")]).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid pattern in rule `broken`"));
+    }
+    #[test]
+    fn pipeline_rules_1() {
+        let pipeline = PipelineBuilder::default()
+            .rules(&rule_set(), Field::Track)
+            .build();
+        assert_eq!(
+            &pipeline.step_names()[..3],
+            ["unicode_normalization", "producer", "free_download"]
+        );
+        assert_eq!(pipeline.clean("Song [FREE DOWNLOAD] (2019) [Mp3]"), "Song");
+        let explanation = pipeline.explain("Song [Free Download]");
+        assert_eq!(explanation.applications[0].rule, "free_download");
+        assert_eq!(explanation.applications[0].span, 5..20);
+    }
+    #[cfg(feature = "config")]
+    #[test]
+    fn rule_set_from_toml_1() {
+        let rules = RuleSet::from_toml(
+            r#"
+            [[rules]]
+            name = "official_video"
+            pattern = '(?i)\(official video\)'
+            field = "track"
+            priority = 5
+            "#,
+        )
+        .unwrap();
+        let rule = rules.rules().next().unwrap();
+        assert_eq!(rule.field, Field::Track);
+        assert_eq!(rule.replacement, " ");
+        assert_eq!(rule.priority, 5);
+        assert!(RuleSet::from_toml("[[rules]]\nname = \"no_pattern\"").is_err());
+    }
+    #[cfg(feature = "config")]
+    #[test]
+    fn rule_set_from_json_1() {
+        let rules = RuleSet::from_json(
+            r#"{"rules": [{"name": "official_video", "pattern": "(?i)\\(official video\\)"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(rules.clean(Field::Album, "Song (Official Video)"), "Song  ");
+    }
+}
@@ -0,0 +1,203 @@
+//! Similarity scores between track titles and between artist lists.
+//!
+//! Both strings are normalized before they are compared, so that two
+//! spellings of the same song, such as `Don't Stop Me Now (Remastered 2011)`
+//! and `Dont Stop Me Now`, score `1.0`. Scores range from `0.0`, for
+//! strings with nothing in common, to `1.0`.
+//!
+use std::collections::HashSet;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{normalize_unicode, parse_track_credits, parse_track_version};
+
+/// Lowercase `cleaned`, remove diacritics and punctuation, and split it into words.
+///
+/// `&` becomes the word `and`, and apostrophes are removed, so that
+/// `Don't` becomes `dont` rather than `don t`.
+pub(crate) fn normalized_words(cleaned: &str) -> Vec<String> {
+    let mut folded = String::with_capacity(cleaned.len());
+    for c in normalize_unicode(cleaned).nfd() {
+        if is_combining_mark(c) || c == '\'' {
+            continue;
+        }
+        if c == '&' {
+            folded.push_str(" and ");
+        } else if c.is_alphanumeric() {
+            folded.extend(c.to_lowercase());
+        } else {
+            folded.push(' ');
+        }
+    }
+    folded.split_whitespace().map(str::to_string).collect()
+}
+
+/// The words of a track title, without credits, version tags or `the`.
+fn title_words(title: &str) -> Vec<String> {
+    let credits = parse_track_credits(title);
+    let versioned = parse_track_version(&credits.title);
+    normalized_words(&versioned.title)
+        .into_iter()
+        .filter(|word| word != "the")
+        .collect()
+}
+
+/// The normalized name of an artist, without featured artists or `the`.
+fn artist_name(artist: &str) -> String {
+    normalized_words(&parse_track_credits(artist).title)
+        .into_iter()
+        .filter(|word| word != "the")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The number of single-character insertions, deletions and substitutions
+/// needed to turn `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// The [`levenshtein`] distance between `a` and `b`, as a similarity from
+/// `0.0` to `1.0` relative to the length of the longer string.
+pub fn edit_similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+/// The share of words that `a` and `b` have in common (Jaccard index).
+fn word_set_similarity(a: &[String], b: &[String]) -> f64 {
+    let a: HashSet<&String> = a.iter().collect();
+    let b: HashSet<&String> = b.iter().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The share of normalized words that `a` and `b` have in common,
+/// regardless of their order.
+pub fn token_similarity(a: &str, b: &str) -> f64 {
+    word_set_similarity(&normalized_words(a), &normalized_words(b))
+}
+
+/// The similarity of two lists of words: the best of their
+/// [`word_set_similarity`] and the [`edit_similarity`] of their sorted words.
+fn words_similarity(a: &[String], b: &[String]) -> f64 {
+    let sorted = |words: &[String]| {
+        let mut words = words.to_vec();
+        words.sort();
+        words.join(" ")
+    };
+    word_set_similarity(a, b).max(edit_similarity(&sorted(a), &sorted(b)))
+}
+
+/// Score how likely two track titles are to name the same song.
+///
+/// Before comparing, both titles are cleaned with
+/// [`crate::parse_track_credits`] and [`crate::parse_track_version`], and
+/// reduced to lowercase words without diacritics, punctuation or `the`.
+/// The score is the better of two metrics:
+/// - the share of words that the titles have in common, which ignores
+///   word order
+/// - the [`edit_similarity`] of the words, which tolerates typos
+///
+/// Featured artists and version tags are ignored, so `Song (feat. X)` and
+/// `Song (Radio Edit)` both score `1.0` against `Song`.
+///
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    words_similarity(&title_words(a), &title_words(b))
+}
+
+/// Score how likely two lists of artists are to name the same artists.
+///
+/// Each artist is reduced to lowercase words without diacritics,
+/// punctuation, `the` or featured artists, so `The Weeknd` matches
+/// `Weeknd` and `Beyoncé feat. JAY-Z` matches `Beyonce`. Every artist of
+/// the shorter list is matched to the most similar artist of the longer
+/// list, by [`edit_similarity`], and the score is the average of these
+/// matches. Artists that only appear in the longer list, such as featured
+/// artists, do not lower the score.
+///
+pub fn artists_similarity<A: AsRef<str>, B: AsRef<str>>(a: &[A], b: &[B]) -> f64 {
+    let names = |artists: &mut dyn Iterator<Item = &str>| {
+        artists
+            .map(artist_name)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+    };
+    let a = names(&mut a.iter().map(AsRef::as_ref));
+    let b = names(&mut b.iter().map(AsRef::as_ref));
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if shorter.is_empty() {
+        return if longer.is_empty() { 1.0 } else { 0.0 };
+    }
+    let total: f64 = shorter
+        .iter()
+        .map(|name| {
+            longer
+                .iter()
+                .map(|other| edit_similarity(name, other))
+                .fold(0.0, f64::max)
+        })
+        .sum();
+    total / shorter.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::similarity::*;
+
+    #[test]
+    fn levenshtein_1() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("Beyoncé", "Beyonce"), 1);
+        assert_eq!(edit_similarity("", ""), 1.0);
+    }
+    #[test]
+    fn title_similarity_1() {
+        assert_eq!(
+            title_similarity("Don't Stop Me Now (Remastered 2011)", "Dont Stop Me Now"),
+            1.0
+        );
+        assert_eq!(
+            title_similarity(
+                "The Less I Know the Better",
+                "Less I Know The Better (feat. X)"
+            ),
+            1.0
+        );
+        assert_eq!(title_similarity("Rock & Roll", "Rock and Roll!"), 1.0);
+    }
+    #[test]
+    fn title_similarity_2() {
+        let typo = title_similarity("Jumpman", "Jumpmna");
+        assert!(typo > 0.7 && typo < 1.0);
+        assert!(title_similarity("Jumpman", "Hotline Bling") < 0.3);
+        assert_eq!(token_similarity("Bling Hotline", "hotline bling"), 1.0);
+    }
+    #[test]
+    fn artists_similarity_1() {
+        assert_eq!(artists_similarity(&["Beyoncé", "JAY-Z"], &["Beyonce"]), 1.0);
+        assert_eq!(artists_similarity(&["The Weeknd"], &["weeknd"]), 1.0);
+        assert_eq!(artists_similarity(&["Drake feat. Future"], &["Drake"]), 1.0);
+        assert!(artists_similarity(&["Drake"], &["Future"]) < 0.3);
+        assert_eq!(artists_similarity::<&str, &str>(&[], &["Drake"]), 0.0);
+    }
+}
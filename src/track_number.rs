@@ -0,0 +1,197 @@
+//! Functions to parse track number prefixes out of track titles.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{fix_common, normalize_unicode, remove_upload_noise};
+
+lazy_static! {
+    // A number at the start of a title is only a track number when it is
+    // followed by a separator, as in `03. Title`, `3) Title` or `03 - Title`,
+    // or when it is zero-padded, as in `03 Title`. This keeps titles such as
+    // `7 Rings`, `99 Problems`, `1-800-273-8255` and `007 Theme` intact. The
+    // same goes for a disc and track, as in `1-05 Title` or `1-12. Title`,
+    // so that `50-50 Love` is kept too.
+    static ref TRACK_NUMBER_PREFIX_REGEX: Regex = Regex::new(concat!(
+        r"^(?:",
+        r"(?i:track)[[:space:]]*(?P<track>[0-9]{1,3})[[:space:]]*[-–.:)_]?[[:space:]]*",
+        r"|(?P<side>[A-F])(?P<side_track>[0-9]{1,2})(?:\.|[[:space:]]*[-–)])[[:space:]]+",
+        r"|(?P<disc>[0-9]{1,2})-(?P<disc_track>0[0-9])(?:\.|[[:space:]]*[-–)])?[[:space:]]+",
+        r"|(?P<separated_disc>[0-9]{1,2})-(?P<separated_disc_track>[0-9]{2})(?:\.|[[:space:]]+[-–])[[:space:]]+",
+        r"|(?P<separated>[0-9]{1,3})(?:\.[[:space:]]+|[[:space:]]*\)[[:space:]]*|[[:space:]]+[-–][[:space:]]+|[[:space:]]*_[[:space:]]*)",
+        r"|(?P<padded>0[0-9])(?:[[:space:]]*[-–.][[:space:]]*|[[:space:]]+)",
+        r")(?P<rest>[^[:space:]].*)$",
+    )).unwrap();
+    // A vinyl side without a separator, as in `A1 Title`. Only accepted in
+    // file names, since titles such as `D12 World` or `B4 Da Storm` start
+    // with the same pattern.
+    static ref BARE_SIDE_PREFIX_REGEX: Regex = Regex::new(
+        r"^(?P<side>[A-F])(?P<side_track>[0-9]{1,2})[[:space:]]+(?P<rest>[^[:space:]].*)$"
+    ).unwrap();
+}
+
+/// A track title with its track number prefix split out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumberedTitle {
+    /// The cleaned track title, without the track number prefix.
+    pub title: String,
+    /// The track number, as in `03. Title` or `A1 Title`.
+    pub track_number: Option<u32>,
+    /// The vinyl side, as in `A1 Title`.
+    pub side: Option<char>,
+    /// The disc number, as in `1-05 Title`.
+    pub disc_number: Option<u32>,
+}
+
+/// Removes a track number prefix from `dirty`, storing it in `numbered`.
+/// The returned string is not cleaned.
+///
+/// With `bare_sides`, a vinyl side without a separator, as in `A1 Title`,
+/// is also removed.
+pub(crate) fn remove_track_number<'a>(
+    dirty: &'a str,
+    numbered: &mut NumberedTitle,
+    bare_sides: bool,
+) -> &'a str {
+    let trimmed = dirty.trim_start();
+    let caps = TRACK_NUMBER_PREFIX_REGEX.captures(trimmed).or_else(|| {
+        if bare_sides {
+            BARE_SIDE_PREFIX_REGEX.captures(trimmed)
+        } else {
+            None
+        }
+    });
+    let caps = match caps {
+        Some(caps) => caps,
+        None => return dirty,
+    };
+    let number = |name: &str| caps.name(name).and_then(|n| n.as_str().parse().ok());
+    numbered.track_number = number("track")
+        .or_else(|| number("side_track"))
+        .or_else(|| number("disc_track"))
+        .or_else(|| number("separated_disc_track"))
+        .or_else(|| number("separated"))
+        .or_else(|| number("padded"));
+    numbered.side = caps
+        .name("side")
+        .and_then(|side| side.as_str().chars().next());
+    numbered.disc_number = number("disc").or_else(|| number("separated_disc"));
+    caps.name("rest").unwrap().as_str()
+}
+
+/// Split a raw track title into the title and its track number prefix.
+///
+/// These prefixes are recognized:
+/// - `03. Title`, `3) Title`, `03 - Title` and `03 Title`
+/// - `Track 7 - Title`
+/// - `A1. Title` and `A1 - Title`, for vinyl sides `A` to `F`
+/// - `1-05 Title` and `1-12. Title`, for disc 1, track 5 or 12
+///
+/// Titles that start with a number that is not separated from the rest of
+/// the title, such as `7 Rings`, `99 Problems` or `50-50 Love`, are left
+/// alone, and so are vinyl sides without a separator, as in `D12 World`;
+/// use [`parse_track_number_with_bare_sides`] to accept those. The
+/// remaining title is cleaned with [`remove_upload_noise`] and [`fix_common`].
+///
+pub fn parse_track_number(dirty: &str) -> NumberedTitle {
+    parse(dirty, false)
+}
+
+/// Like [`parse_track_number`], but also recognizes vinyl sides without a
+/// separator, as in `A1 Title` or `B12 Title`.
+///
+/// This suits sources where every title is numbered, such as the tracks of
+/// a vinyl rip. Elsewhere, titles that start like a vinyl side lose their
+/// first word: `D12 World` becomes `World` on side D, track 12, and
+/// `B4 Da Storm` becomes `Da Storm`.
+///
+pub fn parse_track_number_with_bare_sides(dirty: &str) -> NumberedTitle {
+    parse(dirty, true)
+}
+
+fn parse(dirty: &str, bare_sides: bool) -> NumberedTitle {
+    let mut numbered = NumberedTitle::default();
+    let title = remove_track_number(dirty, &mut numbered, bare_sides);
+    numbered.title = fix_common(&remove_upload_noise(&normalize_unicode(title)));
+    numbered
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::track_number::*;
+
+    fn parts(dirty: &str) -> (String, Option<u32>, Option<char>, Option<u32>) {
+        let actual = parse_track_number(dirty);
+        (
+            actual.title,
+            actual.track_number,
+            actual.side,
+            actual.disc_number,
+        )
+    }
+
+    #[test]
+    fn parse_track_number_1() {
+        let expected = ("Title".to_string(), Some(3), None, None);
+        assert_eq!(parts("03. Title"), expected);
+        assert_eq!(parts("03 - Title"), expected);
+        assert_eq!(parts("03 Title"), expected);
+        assert_eq!(parts("3) Title"), expected);
+        assert_eq!(parts("Track 3 - Title"), expected);
+    }
+    #[test]
+    fn parse_track_number_2() {
+        assert_eq!(
+            parts("A1. Title"),
+            ("Title".to_string(), Some(1), Some('A'), None)
+        );
+        assert_eq!(
+            parts("B12 - Title"),
+            ("Title".to_string(), Some(12), Some('B'), None)
+        );
+        assert_eq!(
+            parts("1-05 Title"),
+            ("Title".to_string(), Some(5), None, Some(1))
+        );
+        assert_eq!(
+            parts("2-12. Title"),
+            ("Title".to_string(), Some(12), None, Some(2))
+        );
+        assert_eq!(
+            parts("2-12 - Title"),
+            ("Title".to_string(), Some(12), None, Some(2))
+        );
+    }
+    #[test]
+    fn parse_track_number_3() {
+        for title in &[
+            "99 Problems",
+            "7 Rings",
+            "1-800-273-8255",
+            "007 Theme",
+            "2001: A Space Odyssey",
+            "D12 World",
+            "B4 Da Storm",
+            "A1 Title",
+            "50-50 Love",
+            "9-11 Blues",
+            "20-20 Vision",
+        ] {
+            assert_eq!(parts(title), (title.to_string(), None, None, None));
+        }
+    }
+    #[test]
+    fn parse_track_number_with_bare_sides_1() {
+        let actual = parse_track_number_with_bare_sides("A1 Title");
+        assert_eq!(actual.title, "Title");
+        assert_eq!(actual.track_number, Some(1));
+        assert_eq!(actual.side, Some('A'));
+        let actual = parse_track_number_with_bare_sides("B12 - Title");
+        assert_eq!(actual.title, "Title");
+        assert_eq!(actual.track_number, Some(12));
+        let actual = parse_track_number_with_bare_sides("99 Problems");
+        assert_eq!(actual.title, "99 Problems");
+        assert_eq!(actual.track_number, None);
+    }
+}
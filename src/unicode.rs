@@ -0,0 +1,140 @@
+//! Unicode normalization and folding of look-alike characters.
+//!
+use std::borrow::Cow;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::Edit;
+
+/// Returns the ASCII replacement for a character that is commonly used in
+/// place of it, `Some('\0')` for characters that should be removed, or
+/// `None` if the character should be kept.
+fn fold_char(c: char) -> Option<char> {
+    match c {
+        // No-break and typographic spaces.
+        '\u{00A0}'
+        | '\u{1680}'
+        | '\u{2000}'..='\u{200A}'
+        | '\u{202F}'
+        | '\u{205F}'
+        | '\u{3000}' => Some(' '),
+        // The soft hyphen, zero-width spaces and the byte order mark. The
+        // zero-width non-joiner and joiner are kept, since they change how
+        // Persian or Indic words and emoji sequences are written.
+        '\u{00AD}' | '\u{200B}' | '\u{2060}' | '\u{FEFF}' => Some('\0'),
+        // Fullwidth and CJK brackets.
+        '（' | '｟' | '⦅' => Some('('),
+        '）' | '｠' | '⦆' => Some(')'),
+        '【' | '〔' | '〖' | '〘' | '［' | '「' | '『' => Some('['),
+        '】' | '〕' | '〗' | '〙' | '］' | '」' | '』' => Some(']'),
+        '｛' => Some('{'),
+        '｝' => Some('}'),
+        // Smart quotes and primes.
+        '‘' | '’' | '‚' | '‛' | '′' | '＇' => Some('\''),
+        '“' | '”' | '„' | '‟' | '″' | '＂' => Some('"'),
+        // Hyphens. En and em dashes are kept, since they separate an
+        // artist from a title.
+        '‐' | '‑' | '‒' | '−' | '－' => Some('-'),
+        _ => None,
+    }
+}
+
+/// Returns `true` for characters that are stylized variants of ordinary
+/// letters, digits or punctuation, and should be replaced by their NFKC
+/// compatibility decomposition.
+///
+/// Other compatibility characters, such as `™` or `²`, are kept as they are.
+fn is_stylized(c: char) -> bool {
+    matches!(c,
+        // Halfwidth and fullwidth forms.
+        '\u{FF01}'..='\u{FFEE}'
+        // Mathematical alphanumeric symbols, such as `𝓣𝔂𝓵𝓮𝓻`.
+        | '\u{1D400}'..='\u{1D7FF}'
+        // Enclosed alphanumerics, such as `Ⓐ` or `①`.
+        | '\u{2460}'..='\u{24FF}'
+        // Letterlike symbols, such as `ℍ` or `ℓ`.
+        | '\u{2100}'..='\u{214F}'
+    ) && c != '™'
+        && c != '℗'
+}
+
+/// Normalize Unicode text so that the other cleaning steps can match it.
+///
+/// This function:
+/// 1. Replaces no-break and typographic spaces with an ASCII space.
+/// 2. Removes soft hyphens and zero-width spaces, but not the zero-width
+///    joiner and non-joiner.
+/// 3. Replaces fullwidth and CJK brackets, such as `（）` and `【】`, with
+///    `()` and `[]`.
+/// 4. Replaces smart quotes and hyphen variants with their ASCII versions.
+/// 5. Replaces stylized letters and digits, such as `𝓣𝔂𝓵𝓮𝓻` or `２０１９`,
+///    with plain ones (NFKC).
+/// 6. Composes everything else into NFC.
+///
+pub fn normalize_unicode(dirty: &str) -> Cow<'_, str> {
+    if dirty.is_ascii() {
+        return Cow::Borrowed(dirty);
+    }
+    let mut folded = String::with_capacity(dirty.len());
+    for c in dirty.chars() {
+        match fold_char(c) {
+            Some('\0') => {}
+            Some(replacement) => folded.push(replacement),
+            None if is_stylized(c) => folded.extend(std::iter::once(c).nfkc()),
+            None => folded.push(c),
+        }
+    }
+    let normalized: String = folded.nfc().collect();
+    if normalized == dirty {
+        Cow::Borrowed(dirty)
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+/// The edits made by [`normalize_unicode`] for folded and stylized characters.
+///
+/// Changes made by NFC composition are not included.
+pub(crate) fn normalize_unicode_edits(dirty: &str) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    for (index, c) in dirty.char_indices() {
+        let replacement: String = match fold_char(c) {
+            Some('\0') => String::new(),
+            Some(replacement) => replacement.to_string(),
+            None if is_stylized(c) => std::iter::once(c).nfkc().collect(),
+            None => continue,
+        };
+        edits.push(Edit {
+            span: index..index + c.len_utf8(),
+            replacement,
+        });
+    }
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::unicode::*;
+
+    #[test]
+    fn normalize_unicode_1() {
+        let actual = normalize_unicode("𝓣𝔂𝓵𝓮𝓻\u{00A0}–\u{200B}ＩＧＯＲ （２０１９）【Mp3】");
+        assert_eq!(actual, "Tyler –IGOR (2019)[Mp3]");
+    }
+    #[test]
+    fn normalize_unicode_2() {
+        let actual = normalize_unicode("Beyonce\u{0301} – Don’t Hurt Yourself™");
+        assert_eq!(actual, "Beyoncé – Don't Hurt Yourself™");
+    }
+    #[test]
+    fn normalize_unicode_3() {
+        assert!(matches!(normalize_unicode("IGOR"), Cow::Borrowed(_)));
+        assert!(matches!(normalize_unicode("Sigur Rós"), Cow::Borrowed(_)));
+    }
+    #[test]
+    fn normalize_unicode_4() {
+        assert_eq!(normalize_unicode("می\u{200C}خواهم"), "می\u{200C}خواهم");
+        assert_eq!(normalize_unicode("👩\u{200D}🎤"), "👩\u{200D}🎤");
+        assert_eq!(normalize_unicode("Hel\u{00AD}lo\u{2060}\u{FEFF}"), "Hello");
+    }
+}
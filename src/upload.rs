@@ -0,0 +1,200 @@
+//! Removal of the noise that video and stream uploads add to track titles.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::brackets::{annotation_edits, is_noise};
+use crate::explain::apply_edits;
+use crate::is_inside_brackets;
+
+lazy_static! {
+    // Promotional tags, as in `(Official Music Video)`, `[HD]` or `*NEW 2019*`,
+    // optionally followed by a year. Only removed when nothing else shares
+    // their brackets or asterisks, so `(Audio)` goes but `(Audio Mix)` stays.
+    static ref PROMO_REGEX: Regex = Regex::new(concat!(
+        r"(?i)\b(?:",
+        r"official(?:[[:space:]]+(?:hd|4k|music|lyrics?|live))?(?:[[:space:]]+(?:music[[:space:]]+)?(?:video|audio|visuali[sz]er|clip))?",
+        r"|(?:music|lyrics?|live)[[:space:]]+video|video[[:space:]]+(?:oficial|officiel|clip)|clip[[:space:]]+officiel",
+        r"|with[[:space:]]+lyrics|lyrics?|audio|video|visuali[sz]er",
+        r"|hd|hq|4k|1080p|720p|free(?:[[:space:]]*(?:dl|download))?",
+        r"|out[[:space:]]+now|new|premiere|exclusive",
+        r")\b(?:[[:space:]]*(?:19|20)[0-9]{2}\b)?",
+    )).unwrap();
+    static ref STARRED_REGEX: Regex = Regex::new(r"\*+([^*]+)\*+").unwrap();
+    // A promotional tag after the last dash, as in `Song - Official Video`.
+    // Single words, as in `Paul McCartney - New`, are more likely a title.
+    static ref DASHED_SUFFIX_REGEX: Regex = Regex::new(r"[[:space:]]+[-–—][[:space:]]+([^-–—]+)$").unwrap();
+    // Hashtags at the end of a title, as in `Song #edm #house`. A hashtag
+    // that starts the title, as in `#SELFIE`, is kept.
+    static ref TRAILING_HASHTAGS_REGEX: Regex =
+        Regex::new(r"(?:[[:space:]]+#[[:alpha:]][[:word:]]*)+[[:space:]]*$").unwrap();
+}
+
+/// Returns `true` for emoji and the pictographs that are used like them,
+/// such as `🔥`, `💯`, `✨` or `♪`, and for the characters that modify them.
+fn is_emoji(c: char) -> bool {
+    matches!(c,
+        '\u{1F000}'..='\u{1FAFF}'
+        | '\u{2600}'..='\u{27BF}'
+        | '\u{2B05}'..='\u{2B07}'
+        | '\u{2B1B}' | '\u{2B1C}' | '\u{2B50}' | '\u{2B55}'
+        // Zero-width joiner, variation selector and keycap.
+        | '\u{200D}' | '\u{FE0F}' | '\u{20E3}'
+        | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+/// Returns `true` for the characters that modify or join emoji, such as
+/// a skin tone, rather than add one.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c,
+        '\u{200D}' | '\u{FE0F}' | '\u{20E3}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+/// Remove the emoji that lead or trail `dirty` or its brackets and
+/// separators, and the emoji that repeat, as in `🔥🔥` or `✨ ✨`.
+///
+/// A single emoji between two words is used as a word, as in `I ❤ NY`,
+/// and is kept.
+fn remove_emoji(dirty: &str) -> String {
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut cleaned = String::with_capacity(dirty.len());
+    let mut last = 0;
+    let mut chars = dirty.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if !is_emoji(c) {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        let mut count = usize::from(!is_emoji_modifier(c));
+        let mut joined = c == '\u{200D}';
+        while let Some(&(index, next)) = chars.peek() {
+            if !is_emoji(next) {
+                break;
+            }
+            // Emoji joined with a zero-width joiner, as in `👨‍👩‍👧`, are one.
+            if !is_emoji_modifier(next) && !joined {
+                count += 1;
+            }
+            joined = next == '\u{200D}';
+            end = index + next.len_utf8();
+            chars.next();
+        }
+        let between_words = is_word(dirty[..start].trim_end().chars().next_back())
+            && is_word(dirty[end..].trim_start().chars().next());
+        if count > 1 || !between_words {
+            cleaned.push_str(&dirty[last..start]);
+            last = end;
+        }
+    }
+    cleaned.push_str(&dirty[last..]);
+    cleaned
+}
+
+/// Remove the noise that video and stream uploads add to track titles,
+/// as in `Artist - Song (Official Music Video) [HD] | Lyrics 🔥 #edm`.
+///
+/// This function removes:
+/// 1. Emoji that lead or trail the title, or that repeat, such as `🔥🔥`.
+///    A single emoji between two words, as in `I ❤ NY`, is kept.
+/// 2. Everything after a `|` that is not inside brackets, such as
+///    `| Lyrics` or a `| Label Name` suffix.
+/// 3. Promotional tags that are alone in brackets or asterisks, such as
+///    `(Official Music Video)`, `(Audio)`, `(Visualizer)`, `[HD]`,
+///    `[FREE DL]` or `*NEW 2019*`, or that follow the last dash, as in
+///    `Song - Official Video`.
+/// 4. Hashtags at the end of the title, such as `#edm #house`.
+///
+/// Credits such as `(Prod. X)` are kept; see [`crate::parse_track_credits`].
+/// This is part of [`crate::fix_track_title`], but not of
+/// [`crate::fix_common`]. Add [`crate::Step::UploadNoise`] to a
+/// [`crate::Pipeline`] to use it elsewhere.
+///
+pub fn remove_upload_noise(dirty: &str) -> String {
+    let mut cleaned = remove_emoji(dirty);
+
+    let pipe = cleaned
+        .match_indices('|')
+        .map(|(index, _)| index)
+        .find(|&index| !is_inside_brackets(&cleaned, index) && !cleaned[..index].trim().is_empty());
+    if let Some(index) = pipe {
+        cleaned.truncate(index);
+    }
+
+    let edits = annotation_edits(&cleaned, &[], &[&PROMO_REGEX])
+        .into_iter()
+        .filter(|edit| PROMO_REGEX.is_match(&cleaned[edit.span.clone()]))
+        .collect::<Vec<_>>();
+    cleaned = apply_edits(&cleaned, &edits);
+
+    cleaned = STARRED_REGEX
+        .replace_all(&cleaned, |caps: &regex::Captures| {
+            if is_noise(&caps[1], &[&PROMO_REGEX]) {
+                " ".to_string()
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned();
+
+    if let Some(caps) = DASHED_SUFFIX_REGEX.captures(&cleaned) {
+        let suffix = &caps[1];
+        if suffix.split_whitespace().count() > 1 && is_noise(suffix, &[&PROMO_REGEX]) {
+            let start = caps.get(0).unwrap().start();
+            cleaned.truncate(start);
+        }
+    }
+
+    TRAILING_HASHTAGS_REGEX.replace(&cleaned, "").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::upload::*;
+
+    fn clean(dirty: &str) -> String {
+        crate::remove_redundant_whitespace(&remove_upload_noise(dirty))
+    }
+
+    #[test]
+    fn remove_upload_noise_1() {
+        assert_eq!(
+            clean("Artist - Song (Official Music Video) [HD] | Lyrics"),
+            "Artist - Song"
+        );
+        assert_eq!(clean("Song (Audio)"), "Song");
+        assert_eq!(clean("Song (Visualizer) [FREE DL]"), "Song");
+        assert_eq!(clean("Song (Prod. X) *NEW 2019*"), "Song (Prod. X)");
+        assert_eq!(clean("🔥🔥 Song 🔥🔥"), "Song");
+        assert_eq!(clean("Song 🔥🔥 Remix"), "Song Remix");
+        assert_eq!(clean("Song ✨ ✨ Remix"), "Song Remix");
+        assert_eq!(clean("Song 🔥 (Official Video)"), "Song");
+        assert_eq!(clean("♪ Song ♪"), "Song");
+        assert_eq!(clean("Song | Label Name"), "Song");
+        assert_eq!(clean("Song - Official Video"), "Song");
+        assert_eq!(clean("Song (Official Video 2019) #edm #house"), "Song");
+    }
+    #[test]
+    fn remove_upload_noise_2() {
+        for title in &[
+            "#SELFIE",
+            "Song (Audio Mix)",
+            "Song (New Dawn)",
+            "Song [Live at Wembley]",
+            "Song - Live",
+            "Paul McCartney - New",
+            "Song - Video Killed the Radio Star",
+            "|Song",
+            "Song (A | B)",
+            "*Song*",
+            "I ❤ NY",
+            "I ❤️ NY",
+            "Gold ☆ Star",
+        ] {
+            assert_eq!(clean(title), *title);
+        }
+    }
+}
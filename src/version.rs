@@ -0,0 +1,291 @@
+//! Functions to parse remix, version and edition tags out of track titles.
+//!
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{fix_track_title, normalize_unicode};
+
+/// The kind of version that a tag such as `(Radio Edit)` describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VersionType {
+    /// `(Original Mix)` or `(Original Version)`.
+    Original,
+    /// `(Radio Edit)`, `(Radio Version)` or `(Radio Mix)`.
+    RadioEdit,
+    /// `(Extended Mix)` or `(Extended Version)`.
+    Extended,
+    /// `(X Remix)`, `(X Mix)`, `(X Bootleg)`, `(X Flip)`, `(Remix by X)`, or
+    /// a mix without a remixer, such as `(Club Mix)`.
+    Remix,
+    /// `(X Edit)`, an edit by someone other than the original artist.
+    Edit,
+    /// `(Live)`, `(Live Version)` or `(Live at Y)`.
+    Live,
+    /// `(Acoustic)` or `(Acoustic Version)`.
+    Acoustic,
+    /// `(Remastered)`, `(Remastered 2011)` or `(2011 Remaster)`.
+    Remastered,
+    /// `(Instrumental)`.
+    Instrumental,
+    /// `- Slowed + Reverb`, `(Slowed)` or `(Reverb)`.
+    SlowedReverb,
+    /// `(Sped Up)` or `(Nightcore)`.
+    SpedUp,
+}
+
+impl VersionType {
+    /// Returns `true` if this version is different audio from the original recording.
+    ///
+    /// An original mix or a remaster is the same recording as the plain
+    /// title, while a remix, an edit or a live take is not.
+    pub fn is_distinct_recording(self) -> bool {
+        !matches!(self, VersionType::Original | VersionType::Remastered)
+    }
+}
+
+/// A single version tag parsed from a track title.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackVersion {
+    /// The kind of version.
+    pub kind: VersionType,
+    /// The remixer or editor, for [`VersionType::Remix`] and [`VersionType::Edit`].
+    pub remixer: Option<String>,
+    /// The year of a remaster, as in `(Remastered 2011)`.
+    pub year: Option<u16>,
+    /// Where a live version was recorded, as in `(Live at Wembley)`.
+    pub location: Option<String>,
+}
+
+/// A track title with its version tags split out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionedTitle {
+    /// The cleaned track title, without any version tags.
+    pub title: String,
+    /// The version tags, in the order they appeared in the title.
+    pub versions: Vec<TrackVersion>,
+}
+
+impl VersionedTitle {
+    /// Returns `true` if both titles likely name the same recording.
+    ///
+    /// The titles must match case-insensitively, and both must have the same
+    /// distinct versions (see [`VersionType::is_distinct_recording`]) with
+    /// the same remixers. So `Song (Original Mix)` and `Song (Remastered 2011)`
+    /// are the same recording as `Song`, but `Song (X Remix)` is not.
+    pub fn is_same_recording(&self, other: &VersionedTitle) -> bool {
+        fn distinct(title: &VersionedTitle) -> Vec<(VersionType, Option<String>)> {
+            let mut versions: Vec<_> = title
+                .versions
+                .iter()
+                .filter(|version| version.kind.is_distinct_recording())
+                .map(|version| {
+                    let remixer = version.remixer.as_ref().map(|name| name.to_lowercase());
+                    (version.kind, remixer)
+                })
+                .collect();
+            versions.sort();
+            versions
+        }
+        self.title.to_lowercase() == other.title.to_lowercase() && distinct(self) == distinct(other)
+    }
+}
+
+lazy_static! {
+    static ref BRACKETED_TAG_REGEX: Regex = Regex::new(r"[\(\[]([^\(\)\[\]]+)[\)\]]").unwrap();
+    static ref DASH_TAG_REGEX: Regex =
+        Regex::new(r"[[:space:]]+[-–—][[:space:]]+([^\(\)\[\]\-–—]+)$").unwrap();
+    static ref YEAR_IN_TAG_REGEX: Regex = Regex::new(r"\b(?:19|20)[0-9]{2}\b").unwrap();
+    static ref SLOWED_REGEX: Regex = Regex::new(r"(?i)\b(?:slowed|reverb)\b").unwrap();
+    static ref SPED_UP_REGEX: Regex =
+        Regex::new(r"(?i)\b(?:sped[[:space:]]+up|nightcore)\b").unwrap();
+    static ref ORIGINAL_REGEX: Regex =
+        Regex::new(r"(?i)^original(?:[[:space:]]+(?:mix|version))?$").unwrap();
+    static ref RADIO_EDIT_REGEX: Regex =
+        Regex::new(r"(?i)^radio[[:space:]]+(?:edit|version|mix)$").unwrap();
+    static ref EXTENDED_REGEX: Regex =
+        Regex::new(r"(?i)^extended(?:[[:space:]]+(?:mix|version|edit))?$").unwrap();
+    static ref REMASTERED_REGEX: Regex = Regex::new(r"(?i)\bremaster(?:ed)?\b").unwrap();
+    static ref INSTRUMENTAL_REGEX: Regex =
+        Regex::new(r"(?i)^instrumental(?:[[:space:]]+version)?$").unwrap();
+    static ref ACOUSTIC_REGEX: Regex =
+        Regex::new(r"(?i)^acoustic(?:[[:space:]]+version)?$").unwrap();
+    static ref LIVE_REGEX: Regex =
+        Regex::new(r"(?i)^live(?:[[:space:]]+version)?(?:[[:space:]]+(?:at|from|in)[[:space:]]+(.+))?$").unwrap();
+    static ref REMIX_BY_REGEX: Regex =
+        Regex::new(r"(?i)^(remix|rmx|edit)(?:ed)?[[:space:]]+by[[:space:]]+(.+)$").unwrap();
+    static ref REMIX_REGEX: Regex =
+        Regex::new(r"(?i)^(?:(.+?)[[:space:]]+)?(remix|rmx|mix|bootleg|flip|edit)$").unwrap();
+    // Words before `Mix` or `Edit` that describe the mix rather than name a
+    // remixer, as in `(Club Mix)` or `(Album Edit)`.
+    static ref MIX_DESCRIPTOR_REGEX: Regex = Regex::new(
+        r"(?i)^(?:club|dub|album|single|clean|dirty|explicit|main|vocal|short|long|full|video|12-inch|7-inch)$"
+    ).unwrap();
+}
+
+impl TrackVersion {
+    fn new(kind: VersionType) -> TrackVersion {
+        TrackVersion {
+            kind,
+            remixer: None,
+            year: None,
+            location: None,
+        }
+    }
+
+    /// Classify the text of a single tag, such as `Skrillex Remix`.
+    fn from_tag(tag: &str) -> Option<TrackVersion> {
+        let tag = tag.trim();
+        if SLOWED_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::SlowedReverb));
+        }
+        if SPED_UP_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::SpedUp));
+        }
+        if ORIGINAL_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::Original));
+        }
+        if RADIO_EDIT_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::RadioEdit));
+        }
+        if EXTENDED_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::Extended));
+        }
+        if REMASTERED_REGEX.is_match(tag) {
+            let mut version = TrackVersion::new(VersionType::Remastered);
+            version.year = YEAR_IN_TAG_REGEX
+                .find(tag)
+                .and_then(|year| year.as_str().parse().ok());
+            return Some(version);
+        }
+        if INSTRUMENTAL_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::Instrumental));
+        }
+        if ACOUSTIC_REGEX.is_match(tag) {
+            return Some(TrackVersion::new(VersionType::Acoustic));
+        }
+        if let Some(caps) = LIVE_REGEX.captures(tag) {
+            let mut version = TrackVersion::new(VersionType::Live);
+            version.location = caps.get(1).map(|location| location.as_str().to_string());
+            return Some(version);
+        }
+        let (keyword, remixer) = if let Some(caps) = REMIX_BY_REGEX.captures(tag) {
+            (caps.get(1), caps.get(2))
+        } else if let Some(caps) = REMIX_REGEX.captures(tag) {
+            (caps.get(2), caps.get(1))
+        } else {
+            return None;
+        };
+        let kind = match keyword.map(|keyword| keyword.as_str().to_lowercase()) {
+            Some(ref keyword) if keyword == "edit" => VersionType::Edit,
+            _ => VersionType::Remix,
+        };
+        let mut version = TrackVersion::new(kind);
+        version.remixer = remixer
+            .map(|remixer| remixer.as_str())
+            .filter(|remixer| !MIX_DESCRIPTOR_REGEX.is_match(remixer))
+            .map(|remixer| remixer.to_string());
+        Some(version)
+    }
+}
+
+/// Split a raw track title into the title and its version tags.
+///
+/// Version tags are recognized inside parentheses or brackets, such as
+/// `(Radio Edit)` or `[Extended Mix]`, and after a trailing dash, such as
+/// `- Slowed + Reverb`. Parentheses and brackets that do not describe a
+/// version are left in the title, which is cleaned with [`fix_track_title`].
+///
+pub fn parse_track_version(dirty: &str) -> VersionedTitle {
+    let mut versions = Vec::new();
+    let dirty = normalize_unicode(dirty);
+    let without_brackets = BRACKETED_TAG_REGEX.replace_all(&dirty, |caps: &regex::Captures| {
+        match TrackVersion::from_tag(&caps[1]) {
+            Some(version) => {
+                versions.push(version);
+                " ".to_string()
+            }
+            None => caps[0].to_string(),
+        }
+    });
+
+    let mut title = without_brackets.to_string();
+    if let Some(caps) = DASH_TAG_REGEX.captures(&without_brackets) {
+        if let Some(version) = TrackVersion::from_tag(&caps[1]) {
+            versions.push(version);
+            title.truncate(caps.get(0).unwrap().start());
+        }
+    }
+
+    VersionedTitle {
+        title: fix_track_title(&title),
+        versions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::version::*;
+
+    #[test]
+    fn parse_track_version_1() {
+        let actual = parse_track_version("Levels (Skrillex Remix) [Extended Mix]");
+        assert_eq!(actual.title, "Levels");
+        assert_eq!(actual.versions.len(), 2);
+        assert_eq!(actual.versions[0].kind, VersionType::Remix);
+        assert_eq!(actual.versions[0].remixer.as_deref(), Some("Skrillex"));
+        assert_eq!(actual.versions[1].kind, VersionType::Extended);
+    }
+    #[test]
+    fn parse_track_version_2() {
+        let actual = parse_track_version("Here Comes the Sun (Remastered 2009)");
+        assert_eq!(actual.title, "Here Comes the Sun");
+        assert_eq!(actual.versions[0].kind, VersionType::Remastered);
+        assert_eq!(actual.versions[0].year, Some(2009));
+    }
+    #[test]
+    fn parse_track_version_3() {
+        let actual = parse_track_version("Heartless (Live at Wembley) - Slowed + Reverb");
+        assert_eq!(actual.title, "Heartless");
+        assert_eq!(actual.versions[0].kind, VersionType::Live);
+        assert_eq!(actual.versions[0].location.as_deref(), Some("Wembley"));
+        assert_eq!(actual.versions[1].kind, VersionType::SlowedReverb);
+    }
+    #[test]
+    fn parse_track_version_4() {
+        let actual = parse_track_version("Song (Interlude) - Part 2");
+        assert_eq!(actual.title, "Song (Interlude) - Part 2");
+        assert!(actual.versions.is_empty());
+    }
+    #[test]
+    fn parse_track_version_5() {
+        let actual = parse_track_version("Song (Club Mix) [Album Edit]");
+        assert_eq!(actual.title, "Song");
+        assert_eq!(actual.versions[0].kind, VersionType::Remix);
+        assert_eq!(actual.versions[0].remixer, None);
+        assert_eq!(actual.versions[1].kind, VersionType::Edit);
+        assert_eq!(actual.versions[1].remixer, None);
+        let actual = parse_track_version("Song (Live Version)");
+        assert_eq!(actual.title, "Song");
+        assert_eq!(actual.versions[0].kind, VersionType::Live);
+        assert_eq!(actual.versions[0].location, None);
+    }
+    #[test]
+    fn parse_track_version_6() {
+        let actual = parse_track_version("Song （Skrillex Remix）");
+        assert_eq!(actual.title, "Song");
+        assert_eq!(actual.versions[0].kind, VersionType::Remix);
+        assert_eq!(actual.versions[0].remixer.as_deref(), Some("Skrillex"));
+    }
+    #[test]
+    fn is_same_recording_1() {
+        let plain = parse_track_version("Strobe");
+        assert!(plain.is_same_recording(&parse_track_version("STROBE (Original Mix)")));
+        assert!(plain.is_same_recording(&parse_track_version("Strobe (2015 Remaster)")));
+        assert!(!plain.is_same_recording(&parse_track_version("Strobe (Radio Edit)")));
+        assert!(!plain.is_same_recording(&parse_track_version("Strobe (Acoustic)")));
+    }
+}
@@ -0,0 +1,176 @@
+//! Context-aware removal of year annotations.
+//!
+use std::borrow::Cow;
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::brackets::bracket_groups;
+use crate::{Cleaner, Edit};
+
+/// Titles that consist of a year, such as Prince's "1999" or Dr. Dre's
+/// "2001", and must not be removed when they trail a string.
+const PROTECTED_YEAR_TITLES: &[&str] = &["1979", "1984", "1989", "1999", "2001", "2012"];
+
+lazy_static! {
+    // A year that is the only thing inside a pair of brackets, as in
+    // `(2019)`, `[2019]` or `{ 2019 }`.
+    pub(crate) static ref BRACKETED_YEAR_REGEX: Regex = Regex::new(
+        r"^[[:space:]]*((?:19|20)[0-9]{2})[[:space:]]*$"
+    ).unwrap();
+    // A year at the end of a string, after a separator, as in `Album - 2019`.
+    static ref TRAILING_YEAR_REGEX: Regex = Regex::new(
+        r"[[:space:]]*[-–—,|/][[:space:]]*[\(\[\{]?((?:19|20)[0-9]{2})[[:punct:]]*[[:space:]]*$"
+    ).unwrap();
+    pub(crate) static ref DEFAULT_YEAR_REMOVER: YearRemover = YearRemover::default();
+}
+
+/// Removes year annotations, without touching years that are part of a title.
+///
+/// A year annotation is either:
+/// - a year alone inside brackets, such as `(2019)` or `[1997]`
+/// - a year at the end of the string after a separator, such as `Album - 2019`
+///
+/// Years anywhere else, as in `2001: A Space Odyssey` or `Blink-182`, are
+/// kept. A trailing year is also kept if it is a protected title, such as
+/// `Prince - 1999`. The built-in protected titles can be extended with
+/// [`YearRemover::protect`].
+///
+/// This is the `year_annotation` step of the default [`crate::Pipeline`].
+/// With the `serde` feature, a missing `protected` field deserializes to
+/// the built-in protected titles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct YearRemover {
+    protected: Vec<String>,
+}
+
+impl Default for YearRemover {
+    fn default() -> Self {
+        YearRemover {
+            protected: PROTECTED_YEAR_TITLES
+                .iter()
+                .map(|title| title.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl YearRemover {
+    /// Never remove `title` when it trails a string.
+    pub fn protect(mut self, title: &str) -> Self {
+        self.protected.push(title.trim().to_string());
+        self
+    }
+
+    /// The titles that are never removed when they trail a string.
+    pub fn protected_titles(&self) -> &[String] {
+        &self.protected
+    }
+
+    fn is_protected(&self, caps: &Captures) -> bool {
+        self.protected
+            .iter()
+            .any(|title| title.as_str() == &caps[1])
+    }
+
+    /// The year annotations that this remover would replace, with their years.
+    fn annotations(&self, dirty: &str) -> Vec<(Edit, u16)> {
+        let bracketed = bracket_groups(dirty).into_iter().filter_map(|group| {
+            let caps = BRACKETED_YEAR_REGEX.captures(&dirty[group.content])?;
+            Some((group.span, caps[1].parse().unwrap()))
+        });
+        let trailing = TRAILING_YEAR_REGEX
+            .captures_iter(dirty)
+            .filter(|caps| !self.is_protected(caps))
+            .map(|caps| (caps.get(0).unwrap().range(), caps[1].parse().unwrap()));
+        let mut annotations: Vec<(Edit, u16)> = bracketed
+            .chain(trailing)
+            .map(|(span, year)| {
+                let edit = Edit {
+                    span,
+                    replacement: " ".to_string(),
+                };
+                (edit, year)
+            })
+            .collect();
+        annotations.sort_by_key(|(edit, _)| (edit.span.start, std::cmp::Reverse(edit.span.end)));
+        // A bracketed year can also be the trailing year, as in `Album - (2019)`.
+        annotations.dedup_by(|(later, _), (earlier, _)| earlier.span.end > later.span.start);
+        annotations
+    }
+
+    /// The year from the first year annotation in `dirty`.
+    pub fn year(&self, dirty: &str) -> Option<u16> {
+        self.annotations(dirty).first().map(|&(_, year)| year)
+    }
+}
+
+impl Cleaner for YearRemover {
+    fn name(&self) -> &str {
+        "year_annotation"
+    }
+
+    fn clean<'a>(&self, dirty: &'a str) -> Cow<'a, str> {
+        let edits = self.edits(dirty);
+        if edits.is_empty() {
+            Cow::Borrowed(dirty)
+        } else {
+            Cow::Owned(crate::explain::apply_edits(dirty, &edits))
+        }
+    }
+
+    fn edits(&self, dirty: &str) -> Vec<Edit> {
+        self.annotations(dirty)
+            .into_iter()
+            .map(|(edit, _)| edit)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::year::*;
+
+    fn clean(dirty: &str) -> String {
+        crate::remove_redundant_whitespace(&YearRemover::default().clean(dirty))
+    }
+
+    #[test]
+    fn year_remover_1() {
+        assert_eq!(clean("IGOR (2019) [Mp3]"), "IGOR [Mp3]");
+        assert_eq!(clean("IGOR [ 2019 ]"), "IGOR");
+        assert_eq!(clean("IGOR 【2019】 {2019}"), "IGOR");
+        assert_eq!(clean("IGOR (2019]"), "IGOR (2019]");
+        assert_eq!(clean("Album - 2019"), "Album");
+        assert_eq!(clean("Album - (2019)"), "Album");
+    }
+    #[test]
+    fn year_remover_2() {
+        for title in &[
+            "Prince - 1999.",
+            "Dr. Dre - 2001!",
+            "(2001: A Space Odyssey)",
+            "Blink-182",
+            "Class of 2019",
+        ] {
+            assert_eq!(clean(title), *title);
+        }
+    }
+    #[test]
+    fn year_remover_3() {
+        let remover = YearRemover::default().protect("2046");
+        assert_eq!(clean("Wong Kar-wai - 2046"), "Wong Kar-wai");
+        assert_eq!(remover.clean("Wong Kar-wai - 2046"), "Wong Kar-wai - 2046");
+        assert_eq!(remover.year("Prince - 1999 (1982)"), Some(1982));
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn year_remover_serde_1() {
+        let remover: YearRemover = serde_json::from_str("{}").unwrap();
+        assert_eq!(remover, YearRemover::default());
+        let step: crate::Step = serde_json::from_str(r#""year_annotation""#).unwrap();
+        assert_eq!(step.name(), remover.name());
+    }
+}